@@ -18,5 +18,13 @@ async fn main() -> Result<()> {
 	);
 	req_login.await?.print().await?;
 
+	let req_logoff = hc.do_post(
+		"/api/logoff",
+		json!({
+			"logoff": true
+		}),
+	);
+	req_logoff.await?.print().await?;
+
 	Ok(())
 }
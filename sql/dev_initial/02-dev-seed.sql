@@ -1,3 +0,0 @@
--- User demo
-INSERT INTO "user" (username)
-VALUES ('demo');
\ No newline at end of file
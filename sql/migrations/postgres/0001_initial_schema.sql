@@ -0,0 +1,55 @@
+-- Base app schema
+-- User
+CREATE TABLE "user" (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    username VARCHAR(128) NOT NULL UNIQUE,
+    -- Auth
+    pwd VARCHAR(256),
+    pwd_salt uuid NOT NULL DEFAULT gen_random_uuid(),
+    token_salt uuid NOT NULL DEFAULT gen_random_uuid()
+);
+-- Task
+CREATE TABLE "task" (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    title VARCHAR(256) NOT NULL
+);
+-- Order
+CREATE TABLE "order" (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES "user" (id) ON DELETE CASCADE,
+    -- FixedFloat order reference
+    ff_id VARCHAR(64) NOT NULL UNIQUE,
+    ff_token VARCHAR(256) NOT NULL,
+    order_type VARCHAR(16) NOT NULL,
+    from_ccy VARCHAR(32) NOT NULL,
+    to_ccy VARCHAR(32) NOT NULL,
+    from_amount VARCHAR(64) NOT NULL,
+    to_amount VARCHAR(64) NOT NULL,
+    to_address TEXT NOT NULL,
+    status VARCHAR(32) NOT NULL
+);
+CREATE INDEX order_owner_id_idx ON "order" (owner_id);
+-- Request Log
+CREATE TABLE request_log (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    uuid uuid NOT NULL,
+    ctime timestamp with time zone NOT NULL DEFAULT now(),
+    line jsonb NOT NULL
+);
+CREATE INDEX request_log_uuid_idx ON request_log (uuid);
+-- Upstream Call (audit of the FixedFloat API calls)
+CREATE TABLE upstream_call (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    ctime timestamp with time zone NOT NULL DEFAULT now(),
+    method VARCHAR(32) NOT NULL,
+    request_json jsonb NOT NULL,
+    http_status INT,
+    code INT,
+    msg TEXT,
+    latency_ms BIGINT NOT NULL,
+    attempt INT NOT NULL,
+    req_uuid uuid,
+    order_id VARCHAR(64)
+);
+CREATE INDEX upstream_call_order_id_idx ON upstream_call (order_id);
+CREATE INDEX upstream_call_req_uuid_idx ON upstream_call (req_uuid);
\ No newline at end of file
@@ -0,0 +1,16 @@
+-- Audit columns: creator/modifier user id (0 for root) and timestamps.
+ALTER TABLE "user"
+    ADD COLUMN cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN ctime timestamp with time zone NOT NULL DEFAULT now(),
+    ADD COLUMN mid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN mtime timestamp with time zone NOT NULL DEFAULT now();
+ALTER TABLE "task"
+    ADD COLUMN cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN ctime timestamp with time zone NOT NULL DEFAULT now(),
+    ADD COLUMN mid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN mtime timestamp with time zone NOT NULL DEFAULT now();
+ALTER TABLE "order"
+    ADD COLUMN cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN ctime timestamp with time zone NOT NULL DEFAULT now(),
+    ADD COLUMN mid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN mtime timestamp with time zone NOT NULL DEFAULT now();
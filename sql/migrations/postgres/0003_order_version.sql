@@ -0,0 +1,2 @@
+-- Optimistic concurrency: incremented by each versioned update.
+ALTER TABLE "order" ADD COLUMN version BIGINT NOT NULL DEFAULT 0;
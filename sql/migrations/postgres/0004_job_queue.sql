@@ -0,0 +1,19 @@
+-- Job queue, replacing the unused task scaffold.
+DROP TABLE "task";
+CREATE TABLE job (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    kind VARCHAR(64) NOT NULL,
+    payload jsonb NOT NULL,
+    -- pending, running, done or dead (attempts exhausted)
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    run_at timestamp with time zone NOT NULL DEFAULT now(),
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL,
+    last_error TEXT,
+    -- Lease of the worker running it, taken over once expired.
+    locked_by VARCHAR(64),
+    locked_until timestamp with time zone,
+    ctime timestamp with time zone NOT NULL DEFAULT now(),
+    mtime timestamp with time zone NOT NULL DEFAULT now()
+);
+CREATE INDEX job_status_run_at_idx ON job (status, run_at);
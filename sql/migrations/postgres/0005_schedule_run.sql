@@ -0,0 +1,10 @@
+-- Last run of each scheduled job, shared by the server instances.
+CREATE TABLE schedule_run (
+    name VARCHAR(64) PRIMARY KEY,
+    started_at timestamp with time zone NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    -- ok or error
+    outcome VARCHAR(16) NOT NULL,
+    error TEXT,
+    run_by VARCHAR(64) NOT NULL
+);
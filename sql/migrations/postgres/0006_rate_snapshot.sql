@@ -0,0 +1,19 @@
+-- FixedFloat price quotes, captured periodically for the rate history.
+CREATE TABLE rate_snapshot (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    ctime timestamp with time zone NOT NULL,
+    -- fixed or float
+    order_type VARCHAR(16) NOT NULL,
+    from_ccy VARCHAR(32) NOT NULL,
+    to_ccy VARCHAR(32) NOT NULL,
+    -- Quoted amount, in from_ccy.
+    amount VARCHAR(64) NOT NULL,
+    rate DOUBLE PRECISION NOT NULL,
+    -- Order limits, in from_ccy.
+    min_amount DOUBLE PRECISION,
+    max_amount DOUBLE PRECISION,
+    -- USD value of the quoted amount.
+    usd DOUBLE PRECISION
+);
+CREATE INDEX rate_snapshot_pair_ctime_idx
+    ON rate_snapshot (from_ccy, to_ccy, order_type, ctime);
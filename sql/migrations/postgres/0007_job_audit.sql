@@ -0,0 +1,5 @@
+-- Creator/modifier user id (0 for root, e.g., the job workers) of the jobs.
+-- The other system tables are append-only records, without them.
+ALTER TABLE job
+    ADD COLUMN cid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN mid BIGINT NOT NULL DEFAULT 0;
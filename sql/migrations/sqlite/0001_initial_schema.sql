@@ -0,0 +1,56 @@
+-- Base app schema (SQLite)
+-- Uuids are 16 bytes blobs, timestamps Rfc3339 texts.
+-- User
+CREATE TABLE "user" (
+    id INTEGER PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    -- Auth
+    pwd TEXT,
+    pwd_salt BLOB NOT NULL DEFAULT (randomblob(16)),
+    token_salt BLOB NOT NULL DEFAULT (randomblob(16))
+);
+-- Task
+CREATE TABLE "task" (
+    id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL
+);
+-- Order
+CREATE TABLE "order" (
+    id INTEGER PRIMARY KEY,
+    owner_id INTEGER NOT NULL REFERENCES "user" (id) ON DELETE CASCADE,
+    -- FixedFloat order reference
+    ff_id TEXT NOT NULL UNIQUE,
+    ff_token TEXT NOT NULL,
+    order_type TEXT NOT NULL,
+    from_ccy TEXT NOT NULL,
+    to_ccy TEXT NOT NULL,
+    from_amount TEXT NOT NULL,
+    to_amount TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    status TEXT NOT NULL
+);
+CREATE INDEX order_owner_id_idx ON "order" (owner_id);
+-- Request Log
+CREATE TABLE request_log (
+    id INTEGER PRIMARY KEY,
+    uuid BLOB NOT NULL,
+    ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    line TEXT NOT NULL
+);
+CREATE INDEX request_log_uuid_idx ON request_log (uuid);
+-- Upstream Call (audit of the FixedFloat API calls)
+CREATE TABLE upstream_call (
+    id INTEGER PRIMARY KEY,
+    ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    method TEXT NOT NULL,
+    request_json TEXT NOT NULL,
+    http_status INTEGER,
+    code INTEGER,
+    msg TEXT,
+    latency_ms INTEGER NOT NULL,
+    attempt INTEGER NOT NULL,
+    req_uuid BLOB,
+    order_id TEXT
+);
+CREATE INDEX upstream_call_order_id_idx ON upstream_call (order_id);
+CREATE INDEX upstream_call_req_uuid_idx ON upstream_call (req_uuid);
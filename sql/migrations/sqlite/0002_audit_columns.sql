@@ -0,0 +1,14 @@
+-- Audit columns: creator/modifier user id (0 for root) and timestamps.
+-- (SQLite only adds one column per statement, with a constant default.)
+ALTER TABLE "user" ADD COLUMN cid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "user" ADD COLUMN ctime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE "user" ADD COLUMN mid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "user" ADD COLUMN mtime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE "task" ADD COLUMN cid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "task" ADD COLUMN ctime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE "task" ADD COLUMN mid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "task" ADD COLUMN mtime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE "order" ADD COLUMN cid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "order" ADD COLUMN ctime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE "order" ADD COLUMN mid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "order" ADD COLUMN mtime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
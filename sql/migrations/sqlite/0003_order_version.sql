@@ -0,0 +1,2 @@
+-- Optimistic concurrency: incremented by each versioned update.
+ALTER TABLE "order" ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
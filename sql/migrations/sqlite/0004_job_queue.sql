@@ -0,0 +1,19 @@
+-- Job queue, replacing the unused task scaffold.
+DROP TABLE "task";
+CREATE TABLE job (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    -- pending, running, done or dead (attempts exhausted)
+    status TEXT NOT NULL DEFAULT 'pending',
+    run_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    last_error TEXT,
+    -- Lease of the worker running it, taken over once expired.
+    locked_by TEXT,
+    locked_until TEXT,
+    ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+CREATE INDEX job_status_run_at_idx ON job (status, run_at);
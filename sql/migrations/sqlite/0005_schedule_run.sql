@@ -0,0 +1,10 @@
+-- Last run of each scheduled job, shared by the server instances.
+CREATE TABLE schedule_run (
+    name TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    -- ok or error
+    outcome TEXT NOT NULL,
+    error TEXT,
+    run_by TEXT NOT NULL
+);
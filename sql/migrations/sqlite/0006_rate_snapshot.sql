@@ -0,0 +1,19 @@
+-- FixedFloat price quotes, captured periodically for the rate history.
+CREATE TABLE rate_snapshot (
+    id INTEGER PRIMARY KEY,
+    ctime TEXT NOT NULL,
+    -- fixed or float
+    order_type TEXT NOT NULL,
+    from_ccy TEXT NOT NULL,
+    to_ccy TEXT NOT NULL,
+    -- Quoted amount, in from_ccy.
+    amount TEXT NOT NULL,
+    rate REAL NOT NULL,
+    -- Order limits, in from_ccy.
+    min_amount REAL,
+    max_amount REAL,
+    -- USD value of the quoted amount.
+    usd REAL
+);
+CREATE INDEX rate_snapshot_pair_ctime_idx
+    ON rate_snapshot (from_ccy, to_ccy, order_type, ctime);
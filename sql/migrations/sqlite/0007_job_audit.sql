@@ -0,0 +1,4 @@
+-- Creator/modifier user id (0 for root, e.g., the job workers) of the jobs.
+-- The other system tables are append-only records, without them.
+ALTER TABLE job ADD COLUMN cid INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE job ADD COLUMN mid INTEGER NOT NULL DEFAULT 0;
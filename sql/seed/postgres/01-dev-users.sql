@@ -0,0 +1,7 @@
+-- User demo (pwd: "demo", scheme 01, rehashed to the latest scheme on login)
+INSERT INTO "user" (username, pwd, pwd_salt)
+VALUES (
+        'demo',
+        '#01#_zwhmLQ-jvZ1c9EOhgRGpdic6hb1nWKcvqxxZIZSx3RPxS6Kj4kEEY-qDtDJ5IX2orkwYq4wzKhp9Cp4wPbZhg',
+        'f05e8961-d6ad-4086-9e78-a6de065e5453'
+    ) ON CONFLICT (username) DO NOTHING;
\ No newline at end of file
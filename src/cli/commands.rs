@@ -0,0 +1,288 @@
+use super::output::{print_fields, print_json, print_qr, print_table, Format};
+use super::{
+	Command, CreateArgs, CurrenciesArgs, MigrateCommand, OrdersCommand, PriceArgs,
+	StatusArgs,
+};
+use crate::clients::fixedfloat::models::{Currency, OrderResponse};
+use crate::clients::FixedFloat;
+use crate::config;
+use crate::ctx::Ctx;
+use crate::model::filter::{Filter, ListOptions};
+use crate::model::order::{OrderBmc, FINAL_STATUSES};
+use crate::model::ModelManager;
+use crate::utils::Secret;
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+/// Run an operator command. `serve` and `config` are run by `main`.
+pub async fn exec(command: Command, format: Format) -> Result<()> {
+	match command {
+		Command::Currencies(args) => currencies(args, format).await,
+		Command::Price(args) => price(args, format).await,
+		Command::Create(args) => create(args, format).await,
+		Command::Status(args) => status(args, format).await,
+		Command::Orders(OrdersCommand::List { status, limit }) => {
+			orders_list(status, limit, format).await
+		}
+		Command::Migrate { command } => {
+			migrate(command.unwrap_or(MigrateCommand::Up), format).await
+		}
+		Command::Serve | Command::Config(_) => {
+			bail!("'serve' and 'config' are not operator commands")
+		}
+	}
+}
+
+async fn new_fixedfloat() -> FixedFloat {
+	FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await
+}
+
+// region:    --- Currencies
+async fn currencies(args: CurrenciesArgs, format: Format) -> Result<()> {
+	let currencies = new_fixedfloat().await.get_available_currencies().await?;
+
+	let currencies: Vec<&Currency> = currencies
+		.data
+		.iter()
+		.filter(|c| !args.send || c.send == 1)
+		.filter(|c| !args.recv || c.recv == 1)
+		.filter(|c| match &args.network {
+			Some(network) => c.network.eq_ignore_ascii_case(network),
+			None => true,
+		})
+		.collect();
+
+	if format == Format::Json {
+		return print_json(&currencies);
+	}
+
+	let rows: Vec<Vec<String>> = currencies
+		.iter()
+		.map(|c| {
+			vec![
+				c.code.to_string(),
+				c.coin.to_string(),
+				c.network.to_string(),
+				c.name.to_string(),
+				yes_no(c.send == 1),
+				yes_no(c.recv == 1),
+			]
+		})
+		.collect();
+	print_table(&["CODE", "COIN", "NETWORK", "NAME", "SEND", "RECV"], &rows);
+
+	Ok(())
+}
+
+fn yes_no(val: bool) -> String {
+	if val { "yes" } else { "no" }.to_string()
+}
+// endregion: --- Currencies
+
+// region:    --- Price
+async fn price(args: PriceArgs, format: Format) -> Result<()> {
+	let rate = new_fixedfloat()
+		.await
+		.get_exchange_rate(
+			args.order_type.order_type(),
+			&args.from,
+			&args.to,
+			&args.direction,
+			&args.amount,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await?;
+
+	if format == Format::Json {
+		return print_json(&rate);
+	}
+
+	let (from, to) = (&rate.data.from, &rate.data.to);
+	let opt = |val: &Option<String>| val.clone().unwrap_or_default();
+	print_fields(&[
+		("type", args.order_type.order_type().to_string()),
+		("send", format!("{} {}", from.amount, from.code)),
+		("receive", format!("{} {}", to.amount, to.code)),
+		("rate", opt(&from.rate)),
+		("min", format!("{} {}", opt(&from.min), from.code)),
+		("max", format!("{} {}", opt(&from.max), from.code)),
+	]);
+
+	Ok(())
+}
+// endregion: --- Price
+
+// region:    --- Create
+async fn create(args: CreateArgs, format: Format) -> Result<()> {
+	let CreateArgs { price, to_address } = args;
+
+	let order = new_fixedfloat()
+		.await
+		.create_order(
+			price.order_type.order_type().to_string(),
+			price.from,
+			price.to,
+			price.direction,
+			price.amount,
+			to_address,
+			None,
+			None,
+			None,
+		)
+		.await?;
+
+	if format == Format::Json {
+		// The token is needed to follow the order.
+		let mut json = serde_json::to_value(&order)?;
+		json["data"]["token"] = order.data.token.expose().to_string().into();
+		return print_json(&json);
+	}
+
+	print_order(&order, true);
+	if let Some(address) = &order.data.from.address {
+		println!();
+		print_qr(address)?;
+	}
+
+	Ok(())
+}
+// endregion: --- Create
+
+// region:    --- Status
+async fn status(args: StatusArgs, format: Format) -> Result<()> {
+	let fixedfloat = new_fixedfloat().await;
+	let token = Secret::new(args.token);
+
+	let mut last_status: Option<String> = None;
+	loop {
+		let order = fixedfloat.order_details(&args.id, &token).await?;
+
+		// -- In watch mode, only print the changes.
+		if last_status.as_deref() != Some(order.data.status.as_str()) {
+			match format {
+				Format::Json => print_json(&order)?,
+				Format::Table if last_status.is_none() => print_order(&order, false),
+				Format::Table => println!("status  {}", order.data.status),
+			}
+			last_status = Some(order.data.status.to_string());
+		}
+
+		if !args.watch || FINAL_STATUSES.contains(&order.data.status.as_str()) {
+			return Ok(());
+		}
+
+		tokio::select! {
+			_ = tokio::time::sleep(Duration::from_secs(args.interval)) => {},
+			_ = tokio::signal::ctrl_c() => return Ok(()),
+		}
+	}
+}
+// endregion: --- Status
+
+fn print_order(order: &OrderResponse, with_token: bool) {
+	let order = &order.data;
+	let mut fields = vec![
+		("id", order.id.to_string()),
+		("type", order.order_type.to_string()),
+		("status", order.status.to_string()),
+		("send", format!("{} {}", order.from.amount, order.from.code)),
+		("receive", format!("{} {}", order.to.amount, order.to.code)),
+		("deposit", order.from.address.clone().unwrap_or_default()),
+		("to", order.to.address.clone().unwrap_or_default()),
+	];
+	if with_token {
+		fields.push(("token", order.token.expose().to_string()));
+	}
+
+	print_fields(&fields);
+}
+
+// region:    --- Orders
+async fn orders_list(
+	status: Option<String>,
+	limit: i64,
+	format: Format,
+) -> Result<()> {
+	let mm = ModelManager::new().await?;
+
+	let filter = status.map(|status| Filter::eq("status", status.to_uppercase()));
+	let list_options = ListOptions {
+		limit: Some(limit),
+		order_bys: Some(vec!["!id".to_string()]),
+		..Default::default()
+	};
+	let orders =
+		OrderBmc::list(&Ctx::root_ctx(), &mm, filter, Some(list_options)).await?;
+
+	if format == Format::Json {
+		return print_json(&orders);
+	}
+
+	let rows: Vec<Vec<String>> = orders
+		.iter()
+		.map(|o| {
+			vec![
+				o.id.to_string(),
+				o.owner_id.to_string(),
+				o.ff_id.to_string(),
+				o.order_type.to_string(),
+				format!("{} {}", o.from_amount, o.from_ccy),
+				format!("{} {}", o.to_amount, o.to_ccy),
+				o.status.to_string(),
+			]
+		})
+		.collect();
+	print_table(
+		&["ID", "OWNER", "FF ID", "TYPE", "SEND", "RECEIVE", "STATUS"],
+		&rows,
+	);
+
+	Ok(())
+}
+// endregion: --- Orders
+
+// region:    --- Migrate
+async fn migrate(command: MigrateCommand, format: Format) -> Result<()> {
+	match command {
+		MigrateCommand::Up => {
+			let versions = ModelManager::new().await?.migrate().await?;
+			println!("{} migration(s) applied {versions:?}", versions.len());
+		}
+		MigrateCommand::Status => {
+			let status = ModelManager::new().await?.migration_status().await?;
+			if format == Format::Json {
+				return print_json(&status);
+			}
+
+			let rows: Vec<Vec<String>> = status
+				.iter()
+				.map(|m| {
+					vec![
+						m.version.to_string(),
+						m.name.to_string(),
+						m.applied_at.clone().unwrap_or("pending".to_string()),
+					]
+				})
+				.collect();
+			print_table(&["VERSION", "NAME", "APPLIED"], &rows);
+		}
+		MigrateCommand::Seed => {
+			ModelManager::new().await?.seed().await?;
+			println!("Seed data loaded");
+		}
+		MigrateCommand::DevReset => {
+			ModelManager::dev_reset().await?;
+			println!("Database recreated, migrated and seeded");
+		}
+	}
+
+	Ok(())
+}
+// endregion: --- Migrate
@@ -0,0 +1,166 @@
+// region:    --- Modules
+
+mod commands;
+mod output;
+
+pub use commands::exec;
+pub use output::Format;
+
+use crate::config::ConfigArgs;
+use clap::{Args, Parser, Subcommand};
+
+// endregion: --- Modules
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Shitcoin <> Lightning exchange service")]
+pub struct Cli {
+	#[command(flatten)]
+	pub config: ConfigArgs,
+
+	/// Output format of the operator commands.
+	#[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+	pub output: Format,
+
+	#[command(subcommand)]
+	pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+	/// Run the web server (default).
+	Serve,
+
+	/// Inspect the configuration.
+	#[command(subcommand)]
+	Config(ConfigCommand),
+
+	/// List the FixedFloat currencies.
+	Currencies(CurrenciesArgs),
+
+	/// Get a FixedFloat price.
+	Price(PriceArgs),
+
+	/// Create a FixedFloat order, and show its deposit address.
+	Create(CreateArgs),
+
+	/// Show a FixedFloat order status.
+	Status(StatusArgs),
+
+	/// Orders stored in the database.
+	#[command(subcommand)]
+	Orders(OrdersCommand),
+
+	/// Apply the pending schema migrations (default), or manage them.
+	Migrate {
+		#[command(subcommand)]
+		command: Option<MigrateCommand>,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+	/// Apply the pending schema migrations.
+	Up,
+
+	/// List the migrations, applied or pending.
+	Status,
+
+	/// Load the seed data.
+	Seed,
+
+	/// DEV ONLY - Drop and recreate the database, then migrate and seed it.
+	/// Requires `database.dev_reset=true` with the dev profile.
+	DevReset,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+	/// Validate the config and print the effective values, secrets redacted.
+	Check,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OrdersCommand {
+	/// List the orders of all users, latest first.
+	List {
+		/// Only the orders with this status (e.g., NEW, DONE).
+		#[arg(long)]
+		status: Option<String>,
+
+		#[arg(long, default_value_t = 50)]
+		limit: i64,
+	},
+}
+
+// region:    --- Args
+#[derive(Debug, Args)]
+pub struct CurrenciesArgs {
+	/// Only the currencies that can be sent.
+	#[arg(long, conflicts_with = "recv")]
+	pub send: bool,
+
+	/// Only the currencies that can be received.
+	#[arg(long)]
+	pub recv: bool,
+
+	#[arg(long)]
+	pub network: Option<String>,
+}
+
+/// `fixed` by default.
+#[derive(Debug, Args)]
+pub struct OrderTypeArgs {
+	#[arg(long, conflicts_with = "float")]
+	pub fixed: bool,
+
+	#[arg(long)]
+	pub float: bool,
+}
+
+impl OrderTypeArgs {
+	pub fn order_type(&self) -> &'static str {
+		if self.float {
+			"float"
+		} else {
+			"fixed"
+		}
+	}
+}
+
+#[derive(Debug, Args)]
+pub struct PriceArgs {
+	pub from: String,
+	pub to: String,
+	pub amount: String,
+
+	#[command(flatten)]
+	pub order_type: OrderTypeArgs,
+
+	/// Whether `amount` is the amount sent (`from`) or received (`to`).
+	#[arg(long, default_value = "from", value_parser = ["from", "to"])]
+	pub direction: String,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateArgs {
+	#[command(flatten)]
+	pub price: PriceArgs,
+
+	/// Address receiving the `to` currency.
+	pub to_address: String,
+}
+
+#[derive(Debug, Args)]
+pub struct StatusArgs {
+	pub id: String,
+	pub token: String,
+
+	/// Poll until the order is done, expired or in emergency.
+	#[arg(long)]
+	pub watch: bool,
+
+	/// Polling interval of `--watch`, in seconds.
+	#[arg(long, default_value_t = 10)]
+	pub interval: u64,
+}
+// endregion: --- Args
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use qrcode::render::unicode;
+use qrcode::QrCode;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+	Table,
+	Json,
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+	println!("{}", serde_json::to_string_pretty(value)?);
+
+	Ok(())
+}
+
+/// Print the rows as a space aligned table, with a header line.
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+	let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+	for row in rows {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(cell.chars().count());
+		}
+	}
+
+	let print_row = |cells: Vec<&str>| {
+		let line = cells
+			.iter()
+			.zip(&widths)
+			.map(|(cell, width)| format!("{cell:<width$}"))
+			.collect::<Vec<_>>()
+			.join("  ");
+		println!("{}", line.trim_end());
+	};
+
+	let separators: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+	print_row(headers.to_vec());
+	print_row(separators.iter().map(String::as_str).collect());
+	for row in rows {
+		print_row(row.iter().map(|c| c.as_str()).collect());
+	}
+}
+
+/// Key / value lines, for a single entity.
+pub fn print_fields(fields: &[(&str, String)]) {
+	let width = fields.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+	for (key, val) in fields {
+		println!("{key:<width$}  {val}");
+	}
+}
+
+/// Render the data as a QR code in the terminal (light on dark).
+pub fn print_qr(data: &str) -> Result<()> {
+	let code = QrCode::new(data)?;
+	let qr = code
+		.render::<unicode::Dense1x2>()
+		.dark_color(unicode::Dense1x2::Light)
+		.light_color(unicode::Dense1x2::Dark)
+		.build();
+	println!("{qr}");
+
+	Ok(())
+}
@@ -0,0 +1,58 @@
+use super::client::FixedFloat;
+use super::models::CurrencyResponse;
+use crate::metrics;
+use anyhow::Result;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long the currency list is served from the cache.
+const CURRENCIES_TTL: Duration = Duration::from_secs(300);
+
+struct CachedCurrencies {
+	fetched_at: Instant,
+	currencies: Arc<CurrencyResponse>,
+}
+
+static CURRENCIES: RwLock<Option<CachedCurrencies>> = RwLock::new(None);
+
+impl FixedFloat {
+	/// The currency list from the cache, fetched from FixedFloat when stale.
+	pub async fn get_available_currencies_cached(
+		&self,
+	) -> Result<Arc<CurrencyResponse>> {
+		if let Some(currencies) = fresh_currencies() {
+			metrics::cache_access("currencies", true);
+			return Ok(currencies);
+		}
+		metrics::cache_access("currencies", false);
+
+		self.refresh_currencies_cache().await
+	}
+
+	/// Fetch the currency list from FixedFloat into the cache.
+	pub async fn refresh_currencies_cache(&self) -> Result<Arc<CurrencyResponse>> {
+		let currencies = Arc::new(self.get_available_currencies().await?);
+		if let Ok(mut cached) = CURRENCIES.write() {
+			*cached = Some(CachedCurrencies {
+				fetched_at: Instant::now(),
+				currencies: currencies.clone(),
+			});
+		}
+
+		Ok(currencies)
+	}
+}
+
+fn fresh_currencies() -> Option<Arc<CurrencyResponse>> {
+	let cached = CURRENCIES.read().ok()?;
+	cached
+		.as_ref()
+		.filter(|c| c.fetched_at.elapsed() < CURRENCIES_TTL)
+		.map(|c| c.currencies.clone())
+}
+
+/// Time since the currency list was last fetched, if ever.
+pub fn currencies_age() -> Option<Duration> {
+	let cached = CURRENCIES.read().ok()?;
+	cached.as_ref().map(|c| c.fetched_at.elapsed())
+}
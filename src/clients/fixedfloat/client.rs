@@ -2,26 +2,63 @@ use anyhow::Result;
 use axum::http::{HeaderMap, HeaderValue};
 use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
 use reqwest::header::{ACCEPT, CONTENT_TYPE};
-use tracing::info;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::metrics;
+use crate::model::upstream_call::{UpstreamCallBmc, UpstreamCallForCreate};
+use crate::model::ModelManager;
+use crate::utils::Secret;
 
 use super::client_config::FixedFloatClientConfig;
+use super::error::Error;
 use super::models::{
 	CreateOrderRequest, CurrencyResponse, ExchangeRateResponse, OrderResponse,
+	ResponseEnvelope,
 };
 
+/// Methods safe to retry, as they do not change any upstream state.
+const IDEMPOTENT_METHODS: &[&str] = &["ccies", "price", "order"];
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Request fields never stored in clear in the upstream call audit.
+const REDACTED_FIELDS: &[&str] = &["token"];
+
 pub struct FixedFloat {
 	pub config: FixedFloatClientConfig,
 	pub client: reqwest::Client,
+
+	// -- Audit
+	mm: Option<ModelManager>,
+	req_uuid: Option<Uuid>,
 }
 
 impl FixedFloat {
-	pub async fn new(api_key: &str, api_secret: &str) -> Self {
+	pub async fn new(api_key: &str, api_secret: &Secret<String>) -> Self {
 		let config =
-			FixedFloatClientConfig::new(api_key.to_string(), api_secret.to_string())
+			FixedFloatClientConfig::new(api_key.to_string(), api_secret.clone())
 				.await;
 		let client = reqwest::Client::new();
 
-		FixedFloat { config, client }
+		FixedFloat {
+			config,
+			client,
+			mm: None,
+			req_uuid: None,
+		}
+	}
+
+	/// Record every call in the `upstream_call` table,
+	/// correlated with the given web request uuid.
+	pub fn with_audit(mut self, mm: ModelManager, req_uuid: Option<Uuid>) -> Self {
+		self.mm = Some(mm);
+		self.req_uuid = req_uuid;
+		self
 	}
 
 	fn sign(data: &str, secret: &str) -> Result<String, openssl::error::ErrorStack> {
@@ -33,17 +70,90 @@ impl FixedFloat {
 		Ok(hex::encode(sign))
 	}
 
+	/// Post to a FixedFloat method, retrying idempotent methods
+	/// when FixedFloat is unavailable or rate limiting.
 	pub async fn post(
 		&self,
 		method: &str,
 		data_json: &str,
 	) -> Result<String, anyhow::Error> {
-		// Generate the signature
-		let sig = match Self::sign(data_json, &self.config.api_secret) {
-			Ok(sign) => sign,
-			Err(e) => return Err(anyhow::Error::new(e)),
+		let max_attempts = if IDEMPOTENT_METHODS.contains(&method) {
+			MAX_ATTEMPTS
+		} else {
+			1
+		};
+
+		let mut attempt = 1;
+		loop {
+			match self.post_attempt(method, data_json, attempt).await {
+				Err(ex) if attempt < max_attempts && is_retryable(&ex) => {
+					debug!("{:<12} - {method} - retry after: {ex}", "UPSTREAM");
+					let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+					tokio::time::sleep(delay).await;
+					attempt += 1;
+				}
+				res => return res,
+			}
+		}
+	}
+
+	async fn post_attempt(
+		&self,
+		method: &str,
+		data_json: &str,
+		attempt: u32,
+	) -> Result<String> {
+		let request_json = serde_json::from_str::<Value>(data_json)
+			.map(|v| redact_fields(v, REDACTED_FIELDS))
+			.unwrap_or(Value::Null);
+
+		let mut call_c = UpstreamCallForCreate {
+			method: method.to_string(),
+			order_id: request_json
+				.get("id")
+				.and_then(|v| v.as_str())
+				.map(|v| v.to_string()),
+			request_json,
+			http_status: None,
+			code: None,
+			msg: None,
+			latency_ms: 0,
+			attempt: attempt as i32,
+			req_uuid: self.req_uuid,
 		};
 
+		let start = Instant::now();
+		let res = self.send(method, data_json, &mut call_c).await;
+		let elapsed = start.elapsed();
+		call_c.latency_ms = elapsed.as_millis() as i64;
+
+		let result = upstream_result(&res);
+		metrics::observe_upstream(method, result, call_c.code, elapsed);
+
+		debug!(
+			"{:<12} - {method} - status: {:?} - code: {:?} - {}ms",
+			"UPSTREAM", call_c.http_status, call_c.code, call_c.latency_ms
+		);
+
+		if let Some(mm) = &self.mm {
+			if let Err(ex) = UpstreamCallBmc::create(mm, call_c).await {
+				error!("Fail to record upstream call - {ex:?}");
+			}
+		}
+
+		res
+	}
+
+	async fn send(
+		&self,
+		method: &str,
+		data_json: &str,
+		call_c: &mut UpstreamCallForCreate,
+	) -> Result<String> {
+		// Generate the signature
+		let sig = Self::sign(data_json, self.config.api_secret.expose())
+			.map_err(|ex| Error::FailToSign(ex.to_string()))?;
+
 		// Create headers
 		let headers = self.headers(sig)?;
 
@@ -55,12 +165,57 @@ impl FixedFloat {
 			.headers(headers)
 			.body(String::from(data_json))
 			.send()
-			.await?;
+			.await
+			.map_err(|ex| Error::Unavailable(ex.to_string()))?;
+		let status = res.status();
+		call_c.http_status = Some(status.as_u16() as i32);
+		let body = res
+			.text()
+			.await
+			.map_err(|ex| Error::Unavailable(ex.to_string()))?;
+
+		// Check the HTTP status, then the FixedFloat response code.
+		if status == StatusCode::TOO_MANY_REQUESTS {
+			return Err(Error::RateLimited.into());
+		}
+		if status.is_server_error() {
+			return Err(Error::Unavailable(format!("HTTP {status}")).into());
+		}
+
+		let envelope: ResponseEnvelope = Self::parse(method, status, &body)?;
+		call_c.code = Some(envelope.code);
+		call_c.msg = Some(envelope.msg.to_string());
+		if let Some(order_id) = envelope.data.as_ref().and_then(|d| d.get("id")) {
+			call_c.order_id = order_id.as_str().map(|v| v.to_string());
+		}
+
+		if envelope.code != 0 {
+			return Err(Error::Rejected {
+				method: method.to_string(),
+				code: envelope.code,
+				msg: envelope.msg,
+			}
+			.into());
+		}
+
 		// Return the response body as a string
-		let body = res.text().await?;
 		Ok(body)
 	}
 
+	fn parse<T: DeserializeOwned>(
+		method: &str,
+		status: StatusCode,
+		body: &str,
+	) -> Result<T> {
+		serde_json::from_str(body).map_err(|_| {
+			Error::BadResponse {
+				method: method.to_string(),
+				status: status.as_u16(),
+			}
+			.into()
+		})
+	}
+
 	fn headers(&self, sig: String) -> Result<HeaderMap, anyhow::Error> {
 		let mut headers = HeaderMap::new();
 		headers.insert("X-API-KEY", HeaderValue::from_str(&self.config.api_key)?);
@@ -79,10 +234,12 @@ impl FixedFloat {
 		let method = "ccies";
 		let data_json = "{}";
 		let response = self.post(method, data_json).await?;
-		let currencies: CurrencyResponse = serde_json::from_str(&response)?;
+		let currencies: CurrencyResponse =
+			Self::parse(method, StatusCode::OK, &response)?;
 		Ok(currencies)
 	}
 
+	#[allow(clippy::too_many_arguments)] // The upstream request fields.
 	pub async fn get_exchange_rate(
 		&self,
 		order_type: &str,
@@ -122,12 +279,13 @@ impl FixedFloat {
 
 		let data_json = data.to_string();
 		let response = self.post(method, &data_json).await?;
-		info!("response: {}", response);
-		let exchange_rate: ExchangeRateResponse = serde_json::from_str(&response)?;
+		let exchange_rate: ExchangeRateResponse =
+			Self::parse(method, StatusCode::OK, &response)?;
 
 		Ok(exchange_rate)
 	}
 
+	#[allow(clippy::too_many_arguments)] // The upstream request fields.
 	pub async fn create_order(
 		&self,
 		order_type: String,
@@ -154,8 +312,7 @@ impl FixedFloat {
 		};
 		let data_json = serde_json::to_string(&data)?;
 		let response = self.post(method, &data_json).await?;
-		info!("response: {}", response);
-		let order: OrderResponse = serde_json::from_str(&response)?;
+		let order: OrderResponse = Self::parse(method, StatusCode::OK, &response)?;
 
 		Ok(order)
 	}
@@ -163,18 +320,53 @@ impl FixedFloat {
 	pub async fn order_details(
 		&self,
 		order_id: &str,
-		token: &str,
+		token: &Secret<String>,
 	) -> Result<OrderResponse> {
 		let method = "order";
 		let data = serde_json::json!({
 			"id": order_id,
-			"token": token,
+			"token": token.expose(),
 		});
 		let data_json = data.to_string();
 		let response = self.post(method, &data_json).await?;
-		info!("response: {}", response);
-		let order: OrderResponse = serde_json::from_str(&response)?;
+		let order: OrderResponse = Self::parse(method, StatusCode::OK, &response)?;
 
 		Ok(order)
 	}
 }
+
+// region:    --- Support
+fn is_retryable(err: &anyhow::Error) -> bool {
+	matches!(
+		err.downcast_ref::<Error>(),
+		Some(Error::Unavailable(_) | Error::RateLimited)
+	)
+}
+
+/// Replace the value of the given top level fields with `"***"`.
+fn redact_fields(mut value: Value, fields: &[&str]) -> Value {
+	if let Some(obj) = value.as_object_mut() {
+		for field in fields {
+			if let Some(v) = obj.get_mut(*field) {
+				*v = Value::String("***".to_string());
+			}
+		}
+	}
+	value
+}
+// endregion: --- Support
+
+/// The `result` label of an upstream call, for the metrics.
+fn upstream_result(res: &Result<String>) -> &'static str {
+	let Err(ex) = res else {
+		return "ok";
+	};
+
+	match ex.downcast_ref::<Error>() {
+		Some(Error::Rejected { .. }) => "rejected",
+		Some(Error::Unavailable(_)) => "unavailable",
+		Some(Error::RateLimited) => "rate_limited",
+		Some(Error::BadResponse { .. }) => "bad_response",
+		_ => "error",
+	}
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+
+/// Errors from calls to the FixedFloat API.
+/// (returned through `anyhow::Error`, downcast by the web layer)
+#[derive(Debug, Serialize)]
+pub enum Error {
+	FailToSign(String),
+
+	/// Could not reach FixedFloat, or it answered with a 5xx.
+	Unavailable(String),
+	/// FixedFloat answered with HTTP 429.
+	RateLimited,
+	/// The response body could not be decoded.
+	BadResponse {
+		method: String,
+		status: u16,
+	},
+	/// FixedFloat processed the call but refused it (`code` != 0).
+	Rejected {
+		method: String,
+		code: i32,
+		msg: String,
+	},
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
@@ -1,6 +1,8 @@
+pub mod cache;
 pub mod client;
 mod client_config;
+mod error;
 pub mod models;
-pub mod supported_currencies;
 
 pub use client::*;
+pub use error::Error;
@@ -1,39 +1,33 @@
+use crate::utils::Secret;
 use serde::{Deserialize, Serialize};
 
+/// The `code` and `msg` common to all FixedFloat responses.
+#[derive(Debug, Deserialize)]
+pub struct ResponseEnvelope {
+	pub code: i32,
+	pub msg: String,
+	pub data: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Currency {
-	code: String,
-	coin: String,
-	network: String,
-	name: String,
-	recv: u8,
-	send: u8,
-	tag: Option<String>,
-	logo: String,
-	color: String,
-	priority: u8,
+	pub code: String,
+	pub coin: String,
+	pub network: String,
+	pub name: String,
+	pub recv: u8,
+	pub send: u8,
+	pub tag: Option<String>,
+	pub logo: String,
+	pub color: String,
+	pub priority: u8,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CurrencyResponse {
-	code: i32,
-	msg: String,
-	data: Vec<Currency>,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ExchangeRateRequest {
-	#[serde(rename = "type")]
-	pub order_type: String,
-	pub from_ccy: String,
-	pub to_ccy: String,
-	pub direction: String,
-	pub amount: String,
-	pub ccies: Option<bool>,
-	pub usd: Option<bool>,
-	pub refcode: Option<String>,
-	pub afftax: Option<f64>,
+	pub code: i32,
+	pub msg: String,
+	pub data: Vec<Currency>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -163,7 +157,7 @@ pub struct OrderData {
 	pub to: OrderCurrency,
 	pub back: BackCurrency,
 	pub emergency: Emergency,
-	pub token: String,
+	pub token: Secret<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -172,29 +166,3 @@ pub struct OrderResponse {
 	pub msg: String,
 	pub data: OrderData,
 }
-
-#[derive(Debug, Deserialize, Serialize)]
-pub enum SupportedCurrency {
-	USDCETH,
-	USDTETH,
-}
-
-impl SupportedCurrency {
-	pub fn to_str(&self) -> String {
-		match self {
-			SupportedCurrency::USDCETH => "USDCETH".to_string(),
-			SupportedCurrency::USDTETH => "USDTETH".to_string(),
-		}
-	}
-
-	pub fn get_contract_address(&self) -> String {
-		match self {
-			SupportedCurrency::USDCETH => {
-				"0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string()
-			}
-			SupportedCurrency::USDTETH => {
-				"0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string()
-			}
-		}
-	}
-}
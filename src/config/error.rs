@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Serialize)]
+pub enum Error {
+	// -- Layers
+	FileRead {
+		path: String,
+		cause: String,
+	},
+	FileParse {
+		path: String,
+		cause: String,
+	},
+	SetInvalid {
+		arg: String,
+	},
+	/// `init` called after the config was loaded (by `init` or `config()`).
+	AlreadyInit,
+
+	// -- Validation
+	/// Every missing or invalid key, as `path: reason`.
+	Invalid {
+		errors: Vec<String>,
+	},
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
@@ -0,0 +1,300 @@
+//! The config layers, merged in order into one json tree:
+//! defaults → YAML file → profile YAML file → env vars → CLI flags.
+
+use super::{ConfigArgs, Error, Result};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_CONFIG_FILE: &str = "config.yml";
+const DEFAULT_PROFILE: &str = "dev";
+const PROFILE_ENV: &str = "SERVICE_PROFILE";
+
+/// Env var to config key. Lists are comma separated.
+const ENV_KEYS: &[(&str, &str)] = &[
+	// -- Server
+	("SERVICE_SERVER_HOST", "server.host"),
+	("SERVICE_SERVER_PORT", "server.port"),
+	// -- Database
+	("SERVICE_DB_URL", "database.url"),
+	("SERVICE_DB_MAX_CONNECTIONS", "database.max_connections"),
+	(
+		"SERVICE_DB_MIGRATE_ON_STARTUP",
+		"database.migrate_on_startup",
+	),
+	("SERVICE_DB_DEV_RESET", "database.dev_reset"),
+	("SERVICE_DB_DEV_RESET_URL", "database.dev_reset_url"),
+	// -- FixedFloat
+	("SERVICE_FIXEDFLOAT_API_KEY", "fixedfloat.api_key"),
+	("SERVICE_FIXEDFLOAT_API_SECRET", "fixedfloat.api_secret"),
+	(
+		"SERVICE_FIXEDFLOAT_CHECK_ON_STARTUP",
+		"fixedfloat.check_on_startup",
+	),
+	// -- Crypt
+	("SERVICE_PWD_KEY", "crypt.pwd_key"),
+	("SERVICE_TOKEN_KEY", "crypt.token_key"),
+	("SERVICE_TOKEN_DURATION_SEC", "crypt.token_duration_sec"),
+	("SERVICE_QUOTE_DURATION_SEC", "crypt.quote_duration_sec"),
+	// -- Web
+	("SERVICE_WEB_FOLDER", "web.folder"),
+	("SERVICE_ADMIN_USERNAMES", "web.admin_usernames"),
+	("SERVICE_METRICS_TOKEN", "web.metrics_token"),
+	// -- Log
+	("SERVICE_LOG_SINKS", "log.sinks"),
+	("SERVICE_LOG_FILE_PATH", "log.file_path"),
+	("SERVICE_LOG_FILE_MAX_BYTES", "log.file_max_bytes"),
+	// -- Rates
+	("SERVICE_RATES_SNAPSHOT_PAIRS", "rates.snapshot_pairs"),
+	(
+		"SERVICE_RATES_SNAPSHOT_INTERVAL_SEC",
+		"rates.snapshot_interval_sec",
+	),
+];
+
+/// The merged tree, with the profile and the sources it was built from.
+pub(super) struct Layers {
+	pub tree: Value,
+	pub profile: String,
+	pub sources: Vec<String>,
+}
+
+/// Reads an env var, `std::env::var` but for the tests.
+pub(super) type EnvFn<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+pub(super) fn load(args: &ConfigArgs, env: EnvFn) -> Result<Layers> {
+	let mut tree = defaults();
+	let mut sources = vec!["defaults".to_string()];
+
+	// -- YAML file (optional unless given explicitly)
+	let file = args.config.as_deref().unwrap_or(DEFAULT_CONFIG_FILE);
+	let file = Path::new(file);
+	if let Some(layer) = read_yaml(file, args.config.is_some())? {
+		merge(&mut tree, layer);
+		sources.push(file.display().to_string());
+	}
+
+	// -- Profile YAML file (optional), e.g., `config.prod.yml`
+	let profile = args
+		.profile
+		.clone()
+		.or_else(|| env(PROFILE_ENV))
+		.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+	let profile_file = file.with_extension(format!("{profile}.yml"));
+	if let Some(layer) = read_yaml(&profile_file, false)? {
+		merge(&mut tree, layer);
+		sources.push(profile_file.display().to_string());
+	}
+
+	// -- Env
+	let mut from_env = false;
+	for (name, key) in ENV_KEYS {
+		if let Some(val) = env(name) {
+			set_key(&mut tree, key, Value::String(val));
+			from_env = true;
+		}
+	}
+	if from_env {
+		sources.push("env".to_string());
+	}
+
+	// -- CLI
+	let mut from_cli = false;
+	let port = args.port.map(|port| port.to_string());
+	let flags = [
+		("server.host", args.host.as_ref()),
+		("server.port", port.as_ref()),
+		("database.url", args.db_url.as_ref()),
+	];
+	for (key, val) in flags {
+		if let Some(val) = val {
+			set_key(&mut tree, key, Value::String(val.to_string()));
+			from_cli = true;
+		}
+	}
+	for arg in args.set.iter() {
+		let (key, val) = arg
+			.split_once('=')
+			.filter(|(key, _)| !key.is_empty())
+			.ok_or(Error::SetInvalid {
+				arg: arg.to_string(),
+			})?;
+		set_key(&mut tree, key, Value::String(val.to_string()));
+		from_cli = true;
+	}
+	if from_cli {
+		sources.push("cli".to_string());
+	}
+
+	Ok(Layers {
+		tree,
+		profile,
+		sources,
+	})
+}
+
+fn defaults() -> Value {
+	json!({
+		"server": {
+			"host": "127.0.0.1",
+			"port": 8080,
+		},
+		"database": {
+			"max_connections": 5,
+			"migrate_on_startup": true,
+			"dev_reset": false,
+		},
+		"fixedfloat": {
+			"check_on_startup": true,
+		},
+		"crypt": {
+			"token_duration_sec": 1800,
+			"quote_duration_sec": 60,
+		},
+		"web": {
+			"folder": "web-folder/",
+			"admin_usernames": [],
+		},
+		"log": {
+			"sinks": ["stdout"],
+			"file_path": "logs/request_log.jsonl",
+			"file_max_bytes": 10485760,
+		},
+		"rates": {
+			"snapshot_pairs": [],
+			"snapshot_interval_sec": 300,
+		},
+	})
+}
+
+fn read_yaml(path: &Path, required: bool) -> Result<Option<Value>> {
+	if !required && !path.exists() {
+		return Ok(None);
+	}
+
+	let content = fs::read_to_string(path).map_err(|ex| Error::FileRead {
+		path: path.display().to_string(),
+		cause: ex.to_string(),
+	})?;
+	let layer =
+		serde_yaml::from_str::<Value>(&content).map_err(|ex| Error::FileParse {
+			path: path.display().to_string(),
+			cause: ex.to_string(),
+		})?;
+
+	Ok(Some(layer))
+}
+
+/// Deep merge `layer` into `base`. Objects are merged key by key,
+/// anything else replaces the base value.
+fn merge(base: &mut Value, layer: Value) {
+	match (base, layer) {
+		(Value::Object(base), Value::Object(layer)) => {
+			for (key, val) in layer {
+				merge(base.entry(key).or_insert(Value::Null), val);
+			}
+		}
+		(base, layer) => *base = layer,
+	}
+}
+
+/// Set the value at a dotted key, e.g., `server.port`.
+fn set_key(tree: &mut Value, key: &str, val: Value) {
+	let mut node = tree;
+	for part in key.split('.') {
+		if !node.is_object() {
+			*node = Value::Object(Map::new());
+		}
+		node = node
+			.as_object_mut()
+			.map(|obj| obj.entry(part).or_insert(Value::Null))
+			.expect("node is an object");
+	}
+	*node = val;
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::collections::HashMap;
+	use std::path::PathBuf;
+	use uuid::Uuid;
+
+	/// A YAML config file, and its `test` profile one, in the temp dir.
+	fn fx_config_files(base: &str, profile: &str) -> Result<PathBuf> {
+		let file =
+			std::env::temp_dir().join(format!("fx-config-{}.yml", Uuid::new_v4()));
+		fs::write(&file, base)?;
+		fs::write(file.with_extension("test.yml"), profile)?;
+
+		Ok(file)
+	}
+
+	#[test]
+	fn test_load_precedence_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let file = fx_config_files(
+			"server: {host: '0.0.0.0', port: 8081}\nweb: {folder: 'fx/'}\n\
+			database: {max_connections: 7}",
+			"server: {port: 8082}\ndatabase: {max_connections: 9}",
+		)?;
+		let fx_env = HashMap::from([
+			("SERVICE_PROFILE", "test"),
+			("SERVICE_SERVER_HOST", "10.0.0.1"),
+			("SERVICE_SERVER_PORT", "8083"),
+		]);
+		let args = ConfigArgs {
+			config: Some(file.display().to_string()),
+			port: Some(8084),
+			..Default::default()
+		};
+
+		// -- Exec
+		let layers = load(&args, &|name| fx_env.get(name).map(|v| v.to_string()))?;
+
+		// -- Check
+		let tree = &layers.tree;
+		assert_eq!(layers.profile, "test");
+		assert_eq!(tree["log"]["sinks"], json!(["stdout"]), "from defaults");
+		assert_eq!(tree["web"]["folder"], "fx/", "from the file");
+		assert_eq!(tree["database"]["max_connections"], 9, "from the profile");
+		assert_eq!(tree["server"]["host"], "10.0.0.1", "from the env");
+		assert_eq!(tree["server"]["port"], "8084", "from the cli");
+		assert_eq!(layers.sources.len(), 5, "sources {:?}", layers.sources);
+
+		fs::remove_file(file.with_extension("test.yml"))?;
+		fs::remove_file(file)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_load_err_set_invalid() -> Result<()> {
+		// -- Setup & Fixtures
+		let args = ConfigArgs {
+			config: Some("fx-missing.yml".to_string()),
+			set: vec!["=8080".to_string()],
+			..Default::default()
+		};
+
+		// -- Exec
+		let res = load(&args, &|_| None);
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::FileRead { .. })),
+			"the explicit file is required"
+		);
+		let args = ConfigArgs {
+			set: vec!["=8080".to_string()],
+			..Default::default()
+		};
+		let res = load(&args, &|_| None);
+		assert!(matches!(res, Err(Error::SetInvalid { .. })));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Serialize)]
+pub enum Error {
+	// -- Key
+	KeyFailHmac,
+
+	// -- Token
+	TokenInvalidFormat,
+	TokenCannotDecodeIdent,
+	TokenCannotDecodeExp,
+	TokenSignatureNotMatching,
+	TokenExpNotIso,
+	TokenExpired,
+
+	// -- Quote
+	QuoteInvalidFormat,
+	QuoteCannotDecode,
+	QuoteSignatureNotMatching,
+	QuoteExpNotIso,
+	QuoteExpired,
+	QuotePairMismatch,
+	QuoteOrderMismatch,
+	QuoteInvalidAmount,
+	QuoteSlippageExceeded {
+		quoted: String,
+		fresh: String,
+		slippage_pct: f64,
+	},
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
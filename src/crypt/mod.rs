@@ -0,0 +1,77 @@
+// region:    --- Modules
+
+mod error;
+pub mod quote;
+pub mod token;
+
+pub use self::error::{Error, Result};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+// endregion: --- Modules
+
+pub struct EncryptContent {
+	pub content: String, // Clear content.
+	pub salt: String,    // Clear salt.
+}
+
+/// HMAC-SHA512 the content and salt with the given key,
+/// and return the b64u encoded result.
+pub fn encrypt_into_b64u(
+	key: &[u8],
+	enc_content: &EncryptContent,
+) -> Result<String> {
+	// -- Finalize and b64u encode.
+	let hmac_result = new_hmac_sha512(key, enc_content)?.finalize();
+	let result_bytes = hmac_result.into_bytes();
+
+	let result = base64_url::encode(&result_bytes);
+
+	Ok(result)
+}
+
+/// Whether the b64u signature is the HMAC-SHA512 of the content and salt,
+/// compared in constant time.
+pub fn verify_b64u(
+	key: &[u8],
+	enc_content: &EncryptContent,
+	signature_b64u: &str,
+) -> Result<bool> {
+	let Ok(signature) = base64_url::decode(signature_b64u) else {
+		return Ok(false);
+	};
+
+	let hmac_sha512 = new_hmac_sha512(key, enc_content)?;
+
+	Ok(hmac_sha512.verify_slice(&signature).is_ok())
+}
+
+/// Whether the two secrets (e.g., static tokens) are equal, compared in
+/// constant time through their HMAC-SHA512 under `key`.
+pub fn secrets_eq(key: &[u8], expected: &str, given: &str) -> Result<bool> {
+	let enc_content = |content: &str| EncryptContent {
+		content: content.to_string(),
+		salt: String::new(),
+	};
+	let expected_b64u = encrypt_into_b64u(key, &enc_content(expected))?;
+
+	verify_b64u(key, &enc_content(given), &expected_b64u)
+}
+
+fn new_hmac_sha512(
+	key: &[u8],
+	enc_content: &EncryptContent,
+) -> Result<Hmac<Sha512>> {
+	let EncryptContent { content, salt } = enc_content;
+
+	// -- Create a HMAC-SHA-512 from key.
+	let mut hmac_sha512 =
+		Hmac::<Sha512>::new_from_slice(key).map_err(|_| Error::KeyFailHmac)?;
+
+	// -- Add content.
+	hmac_sha512.update(content.as_bytes());
+	hmac_sha512.update(salt.as_bytes());
+
+	Ok(hmac_sha512)
+}
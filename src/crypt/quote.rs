@@ -0,0 +1,247 @@
+use crate::clients::fixedfloat::models::ExchangeRateResponse;
+use crate::config;
+use crate::crypt::{encrypt_into_b64u, verify_b64u, EncryptContent, Error, Result};
+use crate::utils::{
+	b64u_decode, b64u_encode, now_utc, now_utc_plus_sec_str, parse_utc,
+};
+use serde::{Deserialize, Serialize};
+
+/// Salt used to separate quote signatures from other TOKEN_KEY signatures.
+const QUOTE_SALT: &str = "quote";
+
+/// A price quote handed to the client by `/api/exchange-rate`
+/// and referenced back by `/api/create-order`.
+///
+/// The token format is `b64u(quote_json).b64u(signature)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+	#[serde(rename = "type")]
+	pub order_type: String,
+	pub from_ccy: String,
+	pub to_ccy: String,
+	pub direction: String,
+	/// Amount requested, in the currency given by `direction`.
+	pub amount: String,
+	pub from_amount: String,
+	pub to_amount: String,
+	/// Expiration, Rfc3339.
+	pub exp: String,
+}
+
+// region:    --- Constructor
+impl Quote {
+	pub fn new(
+		order_type: &str,
+		direction: &str,
+		amount: &str,
+		rate: &ExchangeRateResponse,
+	) -> Self {
+		Quote {
+			order_type: order_type.to_string(),
+			from_ccy: rate.data.from.code.to_string(),
+			to_ccy: rate.data.to.code.to_string(),
+			direction: direction.to_string(),
+			amount: amount.to_string(),
+			from_amount: rate.data.from.amount.to_string(),
+			to_amount: rate.data.to.amount.to_string(),
+			exp: now_utc_plus_sec_str(config().crypt.quote_duration_sec),
+		}
+	}
+}
+// endregion: --- Constructor
+
+// region:    --- Token
+impl Quote {
+	/// Serialize and sign the quote into a token string.
+	pub fn to_token(&self) -> Result<String> {
+		let payload =
+			serde_json::to_string(self).map_err(|_| Error::QuoteInvalidFormat)?;
+		let payload_b64u = b64u_encode(&payload);
+		let signature_b64u = sign_payload(&payload_b64u)?;
+
+		Ok(format!("{payload_b64u}.{signature_b64u}"))
+	}
+
+	/// Parse a quote token, verifying its signature and expiration.
+	pub fn from_token(token: &str) -> Result<Quote> {
+		let (payload_b64u, signature_b64u) =
+			token.split_once('.').ok_or(Error::QuoteInvalidFormat)?;
+
+		if !verify_payload(payload_b64u, signature_b64u)? {
+			return Err(Error::QuoteSignatureNotMatching);
+		}
+
+		let payload =
+			b64u_decode(payload_b64u).map_err(|_| Error::QuoteCannotDecode)?;
+		let quote: Quote =
+			serde_json::from_str(&payload).map_err(|_| Error::QuoteCannotDecode)?;
+
+		let exp = parse_utc(&quote.exp).map_err(|_| Error::QuoteExpNotIso)?;
+		if exp < now_utc() {
+			return Err(Error::QuoteExpired);
+		}
+
+		Ok(quote)
+	}
+}
+
+fn sign_payload(payload_b64u: &str) -> Result<String> {
+	encrypt_into_b64u(
+		config().crypt.token_key.expose(),
+		&quote_content(payload_b64u),
+	)
+}
+
+fn verify_payload(payload_b64u: &str, signature_b64u: &str) -> Result<bool> {
+	verify_b64u(
+		config().crypt.token_key.expose(),
+		&quote_content(payload_b64u),
+		signature_b64u,
+	)
+}
+
+fn quote_content(payload_b64u: &str) -> EncryptContent {
+	EncryptContent {
+		content: payload_b64u.to_string(),
+		salt: QUOTE_SALT.to_string(),
+	}
+}
+// endregion: --- Token
+
+// region:    --- Checks
+impl Quote {
+	pub fn check_pair(&self, from_ccy: &str, to_ccy: &str) -> Result<()> {
+		if self.from_ccy != from_ccy || self.to_ccy != to_ccy {
+			return Err(Error::QuotePairMismatch);
+		}
+
+		Ok(())
+	}
+
+	/// The order, fixed on its `direction` side (`from` the amount sent,
+	/// `to` the amount received), must be for the quoted amount of that side.
+	pub fn check_order(&self, direction: &str, amount: &str) -> Result<()> {
+		let quoted = if direction == "to" {
+			&self.to_amount
+		} else {
+			&self.from_amount
+		};
+		if parse_amount(quoted)? != parse_amount(amount)? {
+			return Err(Error::QuoteOrderMismatch);
+		}
+
+		Ok(())
+	}
+
+	/// Compare the quote against the fresh upstream price of the order,
+	/// fixed on its `direction` side.
+	///
+	/// When the send amount is fixed (direction `from`), the receive amount
+	/// must not drop more than `slippage_pct`. When the receive amount is
+	/// fixed (direction `to`), the send amount must not rise more than it.
+	pub fn check_slippage(
+		&self,
+		fresh: &ExchangeRateResponse,
+		direction: &str,
+		slippage_pct: f64,
+	) -> Result<()> {
+		let tolerance = slippage_pct / 100.;
+
+		let (quoted, fresh, exceeded) = if direction == "to" {
+			let quoted = parse_amount(&self.from_amount)?;
+			let fresh = parse_amount(&fresh.data.from.amount)?;
+			(quoted, fresh, fresh > quoted * (1. + tolerance))
+		} else {
+			let quoted = parse_amount(&self.to_amount)?;
+			let fresh = parse_amount(&fresh.data.to.amount)?;
+			(quoted, fresh, fresh < quoted * (1. - tolerance))
+		};
+
+		if exceeded {
+			return Err(Error::QuoteSlippageExceeded {
+				quoted: quoted.to_string(),
+				fresh: fresh.to_string(),
+				slippage_pct,
+			});
+		}
+
+		Ok(())
+	}
+}
+
+fn parse_amount(amount: &str) -> Result<f64> {
+	amount.parse::<f64>().map_err(|_| Error::QuoteInvalidAmount)
+}
+// endregion: --- Checks
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	fn fx_quote() -> Quote {
+		Quote {
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			direction: "from".to_string(),
+			amount: "0.01".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			exp: now_utc_plus_sec_str(60.),
+		}
+	}
+
+	#[test]
+	fn test_token_ok() -> Result<()> {
+		// -- Exec
+		let token = fx_quote().to_token()?;
+		let quote = Quote::from_token(&token)?;
+
+		// -- Check
+		assert_eq!(quote.amount, "0.01");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_token_err_tampered() -> Result<()> {
+		// -- Setup & Fixtures
+		let token = fx_quote().to_token()?;
+		let (_, signature_b64u) = token.split_once('.').unwrap();
+		let mut fx_quote = fx_quote();
+		fx_quote.amount = "10".to_string();
+		let payload_b64u = b64u_encode(&serde_json::to_string(&fx_quote)?);
+		let fx_token = format!("{payload_b64u}.{signature_b64u}");
+
+		// -- Exec
+		let res = Quote::from_token(&fx_token);
+
+		// -- Check
+		assert!(matches!(res, Err(Error::QuoteSignatureNotMatching)));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_check_order_err_mismatch() -> Result<()> {
+		// -- Setup & Fixtures
+		let quote = fx_quote();
+
+		// -- Exec & Check
+		quote.check_order("to", "0.00990")?;
+		quote.check_order("from", "0.010")?;
+		for (direction, amount) in [("to", "0.01"), ("from", "0.0099")] {
+			let res = quote.check_order(direction, amount);
+			assert!(
+				matches!(res, Err(Error::QuoteOrderMismatch)),
+				"{direction} {amount} should mismatch"
+			);
+		}
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
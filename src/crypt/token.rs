@@ -0,0 +1,225 @@
+use crate::config;
+use crate::crypt::{encrypt_into_b64u, verify_b64u, EncryptContent, Error, Result};
+use crate::utils::{
+	b64u_decode, b64u_encode, now_utc, now_utc_plus_sec_str, parse_utc,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+
+// region:    --- Token Type
+
+/// String format: `ident_b64u.exp_b64u.sign_b64u`
+#[derive(Debug)]
+pub struct Token {
+	pub ident: String,     // Identifier (username for example).
+	pub exp: String,       // Expiration date in Rfc3339.
+	pub sign_b64u: String, // Signature, base64url encoded.
+}
+
+impl FromStr for Token {
+	type Err = Error;
+
+	fn from_str(token_str: &str) -> std::result::Result<Self, Self::Err> {
+		let splits: Vec<&str> = token_str.split('.').collect();
+		if splits.len() != 3 {
+			return Err(Error::TokenInvalidFormat);
+		}
+		let (ident_b64u, exp_b64u, sign_b64u) = (splits[0], splits[1], splits[2]);
+
+		Ok(Self {
+			ident: b64u_decode(ident_b64u)
+				.map_err(|_| Error::TokenCannotDecodeIdent)?,
+
+			exp: b64u_decode(exp_b64u).map_err(|_| Error::TokenCannotDecodeExp)?,
+
+			sign_b64u: sign_b64u.to_string(),
+		})
+	}
+}
+
+impl Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}.{}.{}",
+			b64u_encode(&self.ident),
+			b64u_encode(&self.exp),
+			self.sign_b64u
+		)
+	}
+}
+
+// endregion: --- Token Type
+
+// region:    --- Web Token Gen and Validation
+
+pub fn generate_web_token(user: &str, salt: &str) -> Result<Token> {
+	let config = &config();
+	_generate_token(
+		user,
+		config.crypt.token_duration_sec,
+		salt,
+		config.crypt.token_key.expose(),
+	)
+}
+
+pub fn validate_web_token(origin_token: &Token, salt: &str) -> Result<()> {
+	let config = &config();
+	_validate_token_sign_and_exp(
+		origin_token,
+		salt,
+		config.crypt.token_key.expose(),
+	)?;
+
+	Ok(())
+}
+
+// endregion: --- Web Token Gen and Validation
+
+// region:    --- (private) Token Gen and Validation
+
+fn _generate_token(
+	ident: &str,
+	duration_sec: f64,
+	salt: &str,
+	key: &[u8],
+) -> Result<Token> {
+	// -- Compute the two first components.
+	let ident = ident.to_string();
+	let exp = now_utc_plus_sec_str(duration_sec);
+
+	// -- Sign the two first components.
+	let sign_b64u = _token_sign_into_b64u(&ident, &exp, salt, key)?;
+
+	Ok(Token {
+		ident,
+		exp,
+		sign_b64u,
+	})
+}
+
+fn _validate_token_sign_and_exp(
+	origin_token: &Token,
+	salt: &str,
+	key: &[u8],
+) -> Result<()> {
+	// -- Validate signature (in constant time).
+	let content = _token_content(&origin_token.ident, &origin_token.exp, salt);
+	if !verify_b64u(key, &content, &origin_token.sign_b64u)? {
+		return Err(Error::TokenSignatureNotMatching);
+	}
+
+	// -- Validate expiration.
+	let origin_exp =
+		parse_utc(&origin_token.exp).map_err(|_| Error::TokenExpNotIso)?;
+	let now = now_utc();
+
+	if origin_exp < now {
+		return Err(Error::TokenExpired);
+	}
+
+	Ok(())
+}
+
+/// Create token signature from token parts and salt.
+fn _token_sign_into_b64u(
+	ident: &str,
+	exp: &str,
+	salt: &str,
+	key: &[u8],
+) -> Result<String> {
+	let signature = encrypt_into_b64u(key, &_token_content(ident, exp, salt))?;
+
+	Ok(signature)
+}
+
+/// The signed content of the token parts and salt.
+fn _token_content(ident: &str, exp: &str, salt: &str) -> EncryptContent {
+	EncryptContent {
+		content: format!("{}.{}", b64u_encode(ident), b64u_encode(exp)),
+		salt: salt.to_string(),
+	}
+}
+
+// endregion: --- (private) Token Gen and Validation
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	const FX_KEY: &[u8] = b"fx-token-key-01";
+	const FX_SALT: &str = "fx-salt-01";
+
+	#[test]
+	fn test_token_round_trip_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_token = _generate_token("fx-user-01", 60., FX_SALT, FX_KEY)?;
+
+		// -- Exec
+		let token: Token = fx_token.to_string().parse()?;
+
+		// -- Check
+		assert_eq!(token.ident, "fx-user-01");
+		assert_eq!(token.exp, fx_token.exp);
+		_validate_token_sign_and_exp(&token, FX_SALT, FX_KEY)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_err_tampered_sign() -> Result<()> {
+		// -- Setup & Fixtures
+		let mut fx_token = _generate_token("fx-user-01", 60., FX_SALT, FX_KEY)?;
+		fx_token.sign_b64u = b64u_encode("fx-sign");
+
+		// -- Exec
+		let res = _validate_token_sign_and_exp(&fx_token, FX_SALT, FX_KEY);
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::TokenSignatureNotMatching)),
+			"should be TokenSignatureNotMatching, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_err_tampered_ident() -> Result<()> {
+		// -- Setup & Fixtures
+		let mut fx_token = _generate_token("fx-user-01", 60., FX_SALT, FX_KEY)?;
+		fx_token.ident = "fx-user-02".to_string();
+		let fx_token: Token = fx_token.to_string().parse()?;
+
+		// -- Exec
+		let res = _validate_token_sign_and_exp(&fx_token, FX_SALT, FX_KEY);
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::TokenSignatureNotMatching)),
+			"should be TokenSignatureNotMatching, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_err_expired() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_token = _generate_token("fx-user-01", -1., FX_SALT, FX_KEY)?;
+
+		// -- Exec
+		let res = _validate_token_sign_and_exp(&fx_token, FX_SALT, FX_KEY);
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::TokenExpired)),
+			"should be TokenExpired, was {res:?}"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,232 @@
+//! Background job runtime: claims the due jobs of the `job` queue, and runs
+//! them by kind, retrying failures with an exponential backoff until they
+//! are dead-lettered (see `model::job`).
+//!
+//! The kinds:
+//! - `order_poll`, following the upstream status of an order.
+//! - `order_reconcile`, enqueued by the scheduler, polling again the open
+//!   orders left without a poll job.
+
+// region:    --- Modules
+
+mod error;
+pub mod order_poll;
+pub mod order_reconcile;
+
+pub use self::error::{Error, Result};
+
+use crate::metrics;
+use crate::model::job::{Job, JobBmc};
+use crate::model::ModelManager;
+use crate::shutdown::{Shutdown, Workers};
+use crate::utils::now_utc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+// endregion: --- Modules
+
+/// Wait between claims, when the queue has no due job.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const CLAIM_BATCH: i64 = 10;
+/// Well over any job run, so a live worker keeps its jobs. A job left by a
+/// stopped (or crashed) worker is taken over once it expires.
+const LEASE: Duration = Duration::from_secs(120);
+const BACKOFF_BASE: Duration = Duration::from_secs(10);
+const BACKOFF_MAX: Duration = Duration::from_secs(3600);
+
+/// What a successful job run asks for next.
+pub enum JobOutcome {
+	Done,
+	/// Run again after the delay, e.g., the next poll of an order.
+	Reschedule(Duration),
+}
+
+/// Spawn the job worker of this server instance.
+pub fn spawn(workers: &mut Workers, mm: ModelManager) {
+	workers.spawn("jobs", |shutdown| run(mm, shutdown));
+}
+
+async fn run(mm: ModelManager, mut shutdown: Shutdown) {
+	let worker_id = format!("worker-{}", Uuid::new_v4());
+	info!("{:<12} - {worker_id} started", "JOBS");
+
+	while !shutdown.is_triggered() {
+		let jobs = match JobBmc::claim(&mm, &worker_id, CLAIM_BATCH, LEASE).await {
+			Ok(jobs) => jobs,
+			Err(ex) => {
+				warn!("Cannot claim jobs - {ex}");
+				Vec::new()
+			}
+		};
+		let idle = jobs.len() < CLAIM_BATCH as usize;
+
+		// -- Lag of the oldest due order poll (0 when none is due).
+		let oldest_poll = jobs
+			.iter()
+			.filter(|job| job.kind == order_poll::KIND)
+			.map(|job| job.run_at)
+			.min();
+		let lag = oldest_poll
+			.and_then(|run_at| (now_utc() - run_at).try_into().ok())
+			.unwrap_or_default();
+		metrics::set_order_poller_lag(lag);
+
+		for job in jobs {
+			// The jobs not run are taken over when their lease expires.
+			if shutdown.is_triggered() {
+				break;
+			}
+			run_job(&mm, &worker_id, job).await;
+		}
+
+		if idle {
+			tokio::select! {
+				_ = shutdown.wait() => {}
+				_ = tokio::time::sleep(POLL_INTERVAL) => {}
+			}
+		}
+	}
+}
+
+async fn run_job(mm: &ModelManager, worker_id: &str, job: Job) {
+	debug!(
+		"{:<12} - {} #{} (attempt {})",
+		"JOB", job.kind, job.id, job.attempts
+	);
+
+	let res = match job.kind.as_str() {
+		order_poll::KIND => order_poll::run(mm, &job.payload).await,
+		order_reconcile::KIND => order_reconcile::run(mm).await,
+		kind => Err(Error::KindUnknown(kind.to_string())),
+	};
+
+	let released = match res {
+		Ok(JobOutcome::Done) => JobBmc::complete(mm, job.id, worker_id).await,
+		Ok(JobOutcome::Reschedule(delay)) => {
+			let run_at = now_utc() + delay;
+			JobBmc::reschedule(mm, job.id, worker_id, run_at).await
+		}
+		Err(ex) if job.attempts >= job.max_attempts => {
+			warn!(
+				"Job {} #{} dead after {} attempts - {ex}",
+				job.kind, job.id, job.attempts
+			);
+			JobBmc::bury(mm, job.id, worker_id, &ex.to_string()).await
+		}
+		Err(ex) => {
+			debug!("{:<12} - {} #{} failed - {ex}", "JOB", job.kind, job.id);
+			let run_at = now_utc() + backoff(job.attempts);
+			JobBmc::fail(mm, job.id, worker_id, run_at, &ex.to_string()).await
+		}
+	};
+
+	if let Err(ex) = released {
+		warn!("Cannot release job #{} - {ex}", job.id);
+	}
+}
+
+/// `BACKOFF_BASE` doubled on each attempt, up to `BACKOFF_MAX`.
+fn backoff(attempts: i32) -> Duration {
+	let doublings = attempts.saturating_sub(1).clamp(0, 16) as u32;
+
+	BACKOFF_BASE.saturating_mul(1 << doublings).min(BACKOFF_MAX)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ctx::Ctx;
+	use crate::model::job::{JobFilter, JobForCreate};
+	use crate::model::order::{OrderBmc, OrderForCreate};
+	use crate::model::user::{User, UserBmc};
+	use anyhow::Result;
+	use order_poll::OrderPoll;
+	use serde_json::json;
+
+	const FX_WORKER: &str = "worker-fx";
+
+	#[test]
+	fn test_backoff_ok() {
+		assert_eq!(backoff(0), BACKOFF_BASE);
+		assert_eq!(backoff(1), BACKOFF_BASE);
+		assert_eq!(backoff(2), BACKOFF_BASE * 2);
+		assert_eq!(backoff(4), BACKOFF_BASE * 8);
+		assert_eq!(backoff(i32::MAX), BACKOFF_MAX);
+	}
+
+	#[tokio::test]
+	async fn test_run_job_order_poll_done() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+		let order_c = OrderForCreate {
+			ff_id: "FX01".to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: "DONE".to_string(),
+		};
+		let order_id = OrderBmc::create(&Ctx::new(demo.id)?, &mm, order_c).await?;
+		let mut job_c = OrderPoll::job(order_id);
+		job_c.run_at = None;
+		JobBmc::enqueue(&Ctx::root_ctx(), &mm, job_c).await?;
+
+		// -- Exec
+		let mut jobs = JobBmc::claim(&mm, FX_WORKER, CLAIM_BATCH, LEASE).await?;
+		let job = jobs.pop().ok_or(anyhow::anyhow!("no job claimed"))?;
+		run_job(&mm, FX_WORKER, job).await;
+
+		// -- Check
+		let jobs = JobBmc::list(&mm, JobFilter::default()).await?;
+		assert_eq!(jobs[0].status, "done");
+		assert_eq!(jobs[0].locked_by, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_run_job_err_retry_or_bury() -> Result<()> {
+		// -- Setup & Fixtures
+		// Failing jobs (invalid payload), with and without attempts left.
+		let mm = ModelManager::new_scratch().await?;
+		let mut ids = Vec::new();
+		for max_attempts in [2, 1] {
+			let job_c = JobForCreate {
+				kind: order_poll::KIND.to_string(),
+				payload: json!({ "order": 1 }),
+				run_at: None,
+				max_attempts: Some(max_attempts),
+			};
+			ids.push(JobBmc::enqueue(&Ctx::root_ctx(), &mm, job_c).await?);
+		}
+
+		// -- Exec
+		let jobs = JobBmc::claim(&mm, FX_WORKER, CLAIM_BATCH, LEASE).await?;
+		for job in jobs {
+			run_job(&mm, FX_WORKER, job).await;
+		}
+
+		// -- Check
+		let jobs = JobBmc::list(&mm, JobFilter::default()).await?;
+		let find = |id: i64| jobs.iter().find(|job| job.id == id);
+		let retried = find(ids[0]).ok_or(anyhow::anyhow!("job missing"))?;
+		assert_eq!(retried.status, "pending");
+		assert!(retried.last_error.is_some(), "error not recorded");
+		assert!(retried.run_at > now_utc() + BACKOFF_BASE / 2, "no backoff");
+		let buried = find(ids[1]).ok_or(anyhow::anyhow!("job missing"))?;
+		assert_eq!(buried.status, "dead");
+		assert!(buried.last_error.is_some(), "error not recorded");
+		assert_eq!(buried.locked_by, None);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,201 @@
+//! Follow the FixedFloat status of an order until it is final, so the
+//! stored order stays in sync even when its owner stops polling it.
+
+use crate::clients::FixedFloat;
+use crate::config;
+use crate::ctx::Ctx;
+use crate::jobs::{Error, JobOutcome, Result};
+use crate::model::job::JobForCreate;
+use crate::model::order::{Order, OrderBmc, OrderForUpdate, FINAL_STATUSES};
+use crate::model::{self, ModelManager};
+use crate::utils::now_utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::debug;
+
+pub const KIND: &str = "order_poll";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Status updates tried, on version conflicts.
+const UPDATE_TRIES: usize = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct OrderPoll {
+	pub order_id: i64,
+}
+
+impl OrderPoll {
+	/// The job following a new order, first run after a poll interval.
+	pub fn job(order_id: i64) -> JobForCreate {
+		JobForCreate {
+			kind: KIND.to_string(),
+			payload: json!(OrderPoll { order_id }),
+			run_at: Some(now_utc() + POLL_INTERVAL),
+			max_attempts: None,
+		}
+	}
+}
+
+pub async fn run(mm: &ModelManager, payload: &Value) -> Result<JobOutcome> {
+	let OrderPoll { order_id } =
+		serde_json::from_value(payload.clone()).map_err(|ex| {
+			Error::PayloadInvalid {
+				kind: KIND,
+				reason: ex.to_string(),
+			}
+		})?;
+
+	let ctx = Ctx::root_ctx();
+	let order = match OrderBmc::get(&ctx, mm, order_id).await {
+		Ok(order) => order,
+		// Deleted since, nothing left to follow.
+		Err(model::Error::EntityNotFound { .. }) => return Ok(JobOutcome::Done),
+		Err(ex) => return Err(ex.into()),
+	};
+	if order.is_final() {
+		return Ok(JobOutcome::Done);
+	}
+
+	let fixedfloat = FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await
+	.with_audit(mm.clone(), None);
+
+	let order_response = fixedfloat
+		.order_details(&order.ff_id, &order.ff_token)
+		.await
+		.map_err(|ex| Error::Upstream(ex.to_string()))?;
+	let status = order_response.data.status.to_string();
+	let is_final = FINAL_STATUSES.contains(&status.as_str());
+
+	update_status(&ctx, mm, order, &status).await?;
+
+	if is_final {
+		Ok(JobOutcome::Done)
+	} else {
+		Ok(JobOutcome::Reschedule(POLL_INTERVAL))
+	}
+}
+
+/// Store the upstream status. On a version conflict, the order was updated
+/// concurrently (e.g., by its owner), so re-read it and retry, up to
+/// `UPDATE_TRIES` times (then the job fails, and is retried).
+async fn update_status(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	mut order: Order,
+	status: &str,
+) -> Result<()> {
+	for tries_left in (0..UPDATE_TRIES).rev() {
+		if order.status == status {
+			return Ok(());
+		}
+
+		let order_u = OrderForUpdate {
+			status: Some(status.to_string()),
+		};
+		match OrderBmc::update(ctx, mm, order.id, order.version, order_u).await {
+			Ok(_) => return Ok(()),
+			Err(model::Error::VersionConflict { current, .. }) if tries_left > 0 => {
+				debug!("order {} updated concurrently (v{current})", order.id);
+				order = OrderBmc::get(ctx, mm, order.id).await?;
+			}
+			Err(ex) => return Err(ex.into()),
+		}
+	}
+
+	Ok(())
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::order::OrderForCreate;
+	use crate::model::user::{User, UserBmc};
+	use anyhow::Result;
+
+	async fn fx_mm_and_ctx() -> Result<(ModelManager, Ctx)> {
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+
+		Ok((mm, Ctx::new(demo.id)?))
+	}
+
+	fn fx_order(status: &str) -> OrderForCreate {
+		OrderForCreate {
+			ff_id: "FX01".to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: status.to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_run_err_payload_invalid() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+
+		// -- Exec
+		let res = run(&mm, &json!({ "order": 1 })).await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::PayloadInvalid { kind: KIND, .. })),
+			"should be invalid"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_run_done_order_gone_or_final() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let final_id = OrderBmc::create(&ctx, &mm, fx_order("DONE")).await?;
+
+		// -- Exec & Check
+		// No upstream call for these.
+		for order_id in [final_id, final_id + 100] {
+			let outcome = run(&mm, &json!(OrderPoll { order_id })).await?;
+			assert!(
+				matches!(outcome, JobOutcome::Done),
+				"order {order_id} should be done"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_update_status_stale_version_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let id = OrderBmc::create(&ctx, &mm, fx_order("NEW")).await?;
+		let stale_order = OrderBmc::get(&ctx, &mm, id).await?;
+		let order_u = OrderForUpdate {
+			status: Some("PENDING".to_string()),
+		};
+		OrderBmc::update(&ctx, &mm, id, stale_order.version, order_u).await?;
+
+		// -- Exec
+		update_status(&ctx, &mm, stale_order, "EXCHANGE").await?;
+
+		// -- Check
+		let order = OrderBmc::get(&ctx, &mm, id).await?;
+		assert_eq!(order.status, "EXCHANGE");
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
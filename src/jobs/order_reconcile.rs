@@ -0,0 +1,140 @@
+//! Poll again the open orders left without a poll job, e.g., placed before
+//! the job queue, or whose job was lost. The orders of a dead poll job are
+//! left to the admins (see the job retry endpoint).
+
+use crate::ctx::Ctx;
+use crate::jobs::order_poll::{self, OrderPoll};
+use crate::jobs::{JobOutcome, Result};
+use crate::model::filter::{Filter, ListOptions, LIST_LIMIT_MAX};
+use crate::model::job::{JobBmc, JobForCreate};
+use crate::model::order::{OrderBmc, FINAL_STATUSES};
+use crate::model::ModelManager;
+use serde_json::json;
+use std::collections::HashSet;
+use tracing::info;
+
+pub const KIND: &str = "order_reconcile";
+
+pub struct OrderReconcile;
+
+impl OrderReconcile {
+	/// The reconciliation job, to run now.
+	pub fn job() -> JobForCreate {
+		JobForCreate {
+			kind: KIND.to_string(),
+			payload: json!({}),
+			run_at: None,
+			max_attempts: None,
+		}
+	}
+}
+
+pub async fn run(mm: &ModelManager) -> Result<JobOutcome> {
+	let ctx = Ctx::root_ctx();
+
+	// -- The orders with a poll job.
+	let polled: HashSet<i64> = JobBmc::list_not_done(mm, order_poll::KIND)
+		.await?
+		.into_iter()
+		.filter_map(|job| serde_json::from_value::<OrderPoll>(job.payload.0).ok())
+		.map(|poll| poll.order_id)
+		.collect();
+
+	// -- The open orders, page by page.
+	let open_filter = Filter::And {
+		and: FINAL_STATUSES
+			.iter()
+			.map(|status| Filter::ne("status", *status))
+			.collect(),
+	};
+	let mut cursor = None;
+	let mut enqueued = 0;
+	loop {
+		let list_options = ListOptions {
+			limit: Some(LIST_LIMIT_MAX),
+			cursor,
+			..Default::default()
+		};
+		let orders =
+			OrderBmc::list(&ctx, mm, Some(open_filter.clone()), Some(list_options))
+				.await?;
+
+		for order in orders.iter().filter(|order| !polled.contains(&order.id)) {
+			JobBmc::enqueue(&ctx, mm, OrderPoll::job(order.id)).await?;
+			enqueued += 1;
+		}
+
+		match orders.last() {
+			Some(last) if orders.len() as i64 == LIST_LIMIT_MAX => {
+				cursor = Some(last.id)
+			}
+			_ => break,
+		}
+	}
+
+	info!("{:<12} - {enqueued} order poll(s) enqueued", "RECONCILE");
+
+	Ok(JobOutcome::Done)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::job::JobFilter;
+	use crate::model::order::OrderForCreate;
+	use crate::model::user::{User, UserBmc};
+	use anyhow::Result;
+
+	fn fx_order(ff_id: &str, status: &str) -> OrderForCreate {
+		OrderForCreate {
+			ff_id: ff_id.to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: status.to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_run_enqueue_unpolled_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+		let ctx = Ctx::new(demo.id)?;
+		let unpolled_id =
+			OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		let polled_id = OrderBmc::create(&ctx, &mm, fx_order("FX02", "NEW")).await?;
+		OrderBmc::create(&ctx, &mm, fx_order("FX03", "DONE")).await?;
+		JobBmc::enqueue(&ctx, &mm, OrderPoll::job(polled_id)).await?;
+
+		// -- Exec
+		run(&mm).await?;
+		run(&mm).await?;
+
+		// -- Check
+		let filter = JobFilter {
+			kind: Some(order_poll::KIND.to_string()),
+			..Default::default()
+		};
+		let mut order_ids: Vec<i64> = JobBmc::list(&mm, filter)
+			.await?
+			.into_iter()
+			.filter_map(|job| {
+				serde_json::from_value::<OrderPoll>(job.payload.0).ok()
+			})
+			.map(|poll| poll.order_id)
+			.collect();
+		order_ids.sort();
+		assert_eq!(order_ids, [unpolled_id, polled_id]);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
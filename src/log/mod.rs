@@ -1,51 +1,63 @@
+// region:    --- Modules
+
+mod redact;
+mod sink;
+
+pub use redact::{redact_secrets, RedactingMakeWriter};
+pub use sink::{init_sinks, LogSink};
+
 use crate::ctx::Ctx;
+use crate::utils::{format_time, now_utc};
+use crate::web::error::{AppError, ClientError};
 use anyhow::Result;
 use axum::http::{Method, Uri};
 use serde::Serialize;
 use serde_json::{json, Value};
 use serde_with::skip_serializing_none;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::debug;
+use tracing::{debug, error};
 use uuid::Uuid;
 
+// endregion: --- Modules
+
 pub async fn log_request(
 	uuid: Uuid,
 	req_method: Method,
 	uri: Uri,
 	ctx: Option<Ctx>,
-	web_error: Option<anyhow::Error>,
-	client_error: Option<anyhow::Error>,
+	web_error: Option<&AppError>,
+	client_error: Option<ClientError>,
 ) -> Result<()> {
-	let timestamp = SystemTime::now()
-		.duration_since(UNIX_EPOCH)
-		.unwrap()
-		.as_millis();
+	let timestamp = format_time(now_utc());
 
-	let error_type = web_error
-		.as_ref()
-		.map(|e| e.to_string())
-		.or_else(|| client_error.as_ref().map(|e| e.to_string()));
-	let error_data = web_error.map(|se| json!(se.to_string()));
+	let error_type = web_error.map(|e| e.to_string());
+	let error_data =
+		web_error.map(|e| json!(redact_secrets(&format!("{e:?}")).to_string()));
 
 	// Create the RequestLogLine
 	let log_line = RequestLogLine {
 		uuid: uuid.to_string(),
-		timestamp: timestamp.to_string(),
+		timestamp,
 
 		http_path: uri.to_string(),
 		http_method: req_method.to_string(),
 
 		user_id: ctx.map(|c| c.user_id()),
 
-		client_error_type: client_error.map(|e| e.to_string()),
+		client_error_type: client_error.map(|e| e.as_ref().to_string()),
 
 		error_type,
 		error_data,
 	};
 
-	debug!("REQUEST LOG LINE:\n{}", json!(log_line));
+	let log_line = json!(log_line);
+	debug!("REQUEST LOG LINE:\n{}", log_line);
 
-	// TODO - Send to cloud-watch.
+	// -- Send to the configured sinks.
+	for sink in sink::sinks() {
+		if let Err(ex) = sink.write(uuid, &log_line).await {
+			error!("Fail to write request log line - {ex:?}");
+		}
+	}
 
 	Ok(())
 }
@@ -54,7 +66,7 @@ pub async fn log_request(
 #[derive(Serialize)]
 struct RequestLogLine {
 	uuid: String,      // uuid string formatted
-	timestamp: String, // (should be iso8601)
+	timestamp: String, // Rfc3339
 
 	// -- User and context attributes.
 	user_id: Option<i64>,
@@ -0,0 +1,51 @@
+use lazy_regex::regex;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use tracing_subscriber::fmt::MakeWriter;
+
+const MASK: &str = "***";
+
+/// Mask the values of the known secret fields, in JSON (`"token": "..."`)
+/// as well as in Debug output (`token: "..."`).
+pub fn redact_secrets(content: &str) -> Cow<'_, str> {
+	regex!(
+		r#"("?\b(?:token|ff_token|api_secret|secret|pwd|pwd_clear|pwd_old|pwd_new)"?\s*:\s*)"(?:[^"\\]|\\.)*""#
+	)
+	.replace_all(content, format!(r#"${{1}}"{MASK}""#))
+}
+
+// region:    --- RedactingMakeWriter
+/// Tracing writer to stderr masking the secret fields of each event.
+pub struct RedactingMakeWriter;
+
+impl<'a> MakeWriter<'a> for RedactingMakeWriter {
+	type Writer = RedactingWriter;
+
+	fn make_writer(&'a self) -> Self::Writer {
+		RedactingWriter { buf: Vec::new() }
+	}
+}
+
+/// Buffers one event, and writes it redacted to stderr when dropped.
+pub struct RedactingWriter {
+	buf: Vec<u8>,
+}
+
+impl Write for RedactingWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.buf.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl Drop for RedactingWriter {
+	fn drop(&mut self) {
+		let content = String::from_utf8_lossy(&self.buf);
+		let _ = io::stderr().write_all(redact_secrets(&content).as_bytes());
+	}
+}
+// endregion: --- RedactingMakeWriter
@@ -0,0 +1,288 @@
+use crate::config;
+use crate::model::request_log::RequestLogBmc;
+use crate::model::ModelManager;
+use crate::shutdown::{Shutdown, Workers};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::mpsc;
+use tracing::error;
+use uuid::Uuid;
+
+/// Number of rotated files kept by the `FileSink` (`path.1` ... `path.N`).
+const FILE_SINK_MAX_ROTATED: usize = 5;
+
+/// Lines the `DbSink` queues for its writer before dropping new ones.
+const DB_SINK_QUEUE_CAPACITY: usize = 1024;
+
+/// A destination for the request log lines.
+#[async_trait]
+pub trait LogSink: Send + Sync {
+	async fn write(&self, uuid: Uuid, line: &Value) -> Result<()>;
+}
+
+// region:    --- Registry
+static SINKS: OnceLock<Vec<Box<dyn LogSink>>> = OnceLock::new();
+
+/// Create the sinks listed in `LOG_SINKS`. Must be called once at startup.
+/// The sink writers are spawned on `workers`, to be drained on shutdown.
+pub fn init_sinks(mm: &ModelManager, workers: &mut Workers) -> Result<()> {
+	let mut sinks: Vec<Box<dyn LogSink>> = Vec::new();
+
+	for name in config().log.sinks.iter() {
+		let sink: Box<dyn LogSink> = match name.as_str() {
+			"stdout" => Box::new(StdoutSink),
+			"file" => Box::new(FileSink::new(
+				&config().log.file_path,
+				config().log.file_max_bytes,
+			)?),
+			"db" => Box::new(DbSink::new(mm.clone(), workers)),
+			other => return Err(anyhow!("Unknown log sink: {other}")),
+		};
+		sinks.push(sink);
+	}
+
+	SINKS
+		.set(sinks)
+		.map_err(|_| anyhow!("Log sinks already initialized"))
+}
+
+/// The configured sinks (none if `init_sinks` was not called).
+pub fn sinks() -> &'static [Box<dyn LogSink>] {
+	SINKS.get().map(|s| s.as_slice()).unwrap_or(&[])
+}
+// endregion: --- Registry
+
+// region:    --- StdoutSink
+pub struct StdoutSink;
+
+#[async_trait]
+impl LogSink for StdoutSink {
+	async fn write(&self, _uuid: Uuid, line: &Value) -> Result<()> {
+		println!("{line}");
+
+		Ok(())
+	}
+}
+// endregion: --- StdoutSink
+
+// region:    --- FileSink
+/// Appends JSON lines to a file, rotating it once it reaches `max_bytes`.
+///
+/// The file is written on the blocking thread pool, not to stall the
+/// runtime threads on the disk.
+pub struct FileSink {
+	writer: Arc<Mutex<FileWriter>>,
+}
+
+struct FileWriter {
+	path: PathBuf,
+	max_bytes: u64,
+	state: FileState,
+}
+
+struct FileState {
+	file: File,
+	size: u64,
+}
+
+impl FileSink {
+	pub fn new(path: &str, max_bytes: u64) -> Result<Self> {
+		let path = PathBuf::from(path);
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let state = open_file(&path)?;
+		let writer = FileWriter {
+			path,
+			max_bytes,
+			state,
+		};
+
+		Ok(FileSink {
+			writer: Arc::new(Mutex::new(writer)),
+		})
+	}
+}
+
+impl FileWriter {
+	fn write(&mut self, line: &str) -> Result<()> {
+		let len = line.len() as u64;
+		if self.state.size > 0 && self.state.size + len > self.max_bytes {
+			self.rotate()?;
+		}
+
+		self.state.file.write_all(line.as_bytes())?;
+		self.state.size += len;
+
+		Ok(())
+	}
+
+	fn rotate(&mut self) -> Result<()> {
+		for idx in (1..FILE_SINK_MAX_ROTATED).rev() {
+			let from = rotated_path(&self.path, idx);
+			if from.exists() {
+				fs::rename(&from, rotated_path(&self.path, idx + 1))?;
+			}
+		}
+		fs::rename(&self.path, rotated_path(&self.path, 1))?;
+
+		self.state = open_file(&self.path)?;
+
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl LogSink for FileSink {
+	async fn write(&self, _uuid: Uuid, line: &Value) -> Result<()> {
+		let line = format!("{line}\n");
+		let writer = self.writer.clone();
+
+		tokio::task::spawn_blocking(move || {
+			writer
+				.lock()
+				.map_err(|_| anyhow!("FileSink lock poisoned"))?
+				.write(&line)
+		})
+		.await?
+	}
+}
+
+fn open_file(path: &Path) -> Result<FileState> {
+	let file = OpenOptions::new().create(true).append(true).open(path)?;
+	let size = file.metadata()?.len();
+
+	Ok(FileState { file, size })
+}
+
+fn rotated_path(path: &Path, idx: usize) -> PathBuf {
+	let mut rotated = path.as_os_str().to_owned();
+	rotated.push(format!(".{idx}"));
+	PathBuf::from(rotated)
+}
+// endregion: --- FileSink
+
+// region:    --- DbSink
+/// Inserts the lines into the `request_log` table.
+///
+/// The lines are queued to a background writer, so the response does not
+/// wait on the database. When the queue is full, new lines are dropped.
+/// On shutdown, the writer inserts the lines still queued before stopping.
+pub struct DbSink {
+	tx: mpsc::Sender<(Uuid, Value)>,
+}
+
+impl DbSink {
+	/// Must be called within the tokio runtime (spawns the writer).
+	pub fn new(mm: ModelManager, workers: &mut Workers) -> Self {
+		let (tx, rx) = mpsc::channel(DB_SINK_QUEUE_CAPACITY);
+		workers.spawn("log-db-sink", |shutdown| db_sink_writer(mm, rx, shutdown));
+
+		DbSink { tx }
+	}
+}
+
+#[async_trait]
+impl LogSink for DbSink {
+	async fn write(&self, uuid: Uuid, line: &Value) -> Result<()> {
+		self.tx
+			.try_send((uuid, line.clone()))
+			.map_err(|ex| anyhow!("DbSink cannot queue line - {ex}"))
+	}
+}
+
+async fn db_sink_writer(
+	mm: ModelManager,
+	mut rx: mpsc::Receiver<(Uuid, Value)>,
+	mut shutdown: Shutdown,
+) {
+	loop {
+		let (uuid, line) = tokio::select! {
+			msg = rx.recv() => match msg {
+				Some(msg) => msg,
+				None => return,
+			},
+			_ = shutdown.wait() => break,
+		};
+		db_sink_insert(&mm, uuid, &line).await;
+	}
+
+	// -- Drain the queue (new lines are refused from now on).
+	rx.close();
+	while let Some((uuid, line)) = rx.recv().await {
+		db_sink_insert(&mm, uuid, &line).await;
+	}
+}
+
+async fn db_sink_insert(mm: &ModelManager, uuid: Uuid, line: &Value) {
+	if let Err(ex) = RequestLogBmc::create(mm, uuid, line).await {
+		error!("DbSink cannot insert line - {ex:?}");
+	}
+}
+// endregion: --- DbSink
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::now_utc;
+	use serde_json::json;
+	use std::time::Duration;
+
+	#[tokio::test]
+	async fn test_db_sink_shutdown_drain_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let mut workers = Workers::new();
+		let sink = DbSink::new(mm.clone(), &mut workers);
+
+		// -- Exec
+		for n in 0..3 {
+			sink.write(Uuid::new_v4(), &json!({ "n": n })).await?;
+		}
+		workers.shutdown(Duration::from_secs(5)).await;
+
+		// -- Check
+		let count =
+			RequestLogBmc::delete_before(&mm, now_utc() + time::Duration::hours(1))
+				.await?;
+		assert_eq!(count, 3);
+		assert!(
+			sink.write(Uuid::new_v4(), &json!({})).await.is_err(),
+			"should refuse lines after shutdown"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_file_sink_rotate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let dir =
+			std::env::temp_dir().join(format!("fixedfloat-{}", Uuid::new_v4()));
+		let path = dir.join("request_log.jsonl");
+		let fx_line = json!({ "n": 0 });
+		let max_bytes = 2 * (fx_line.to_string().len() as u64 + 1);
+		let sink = FileSink::new(path.to_str().unwrap_or_default(), max_bytes)?;
+
+		// -- Exec
+		for _ in 0..3 {
+			sink.write(Uuid::new_v4(), &fx_line).await?;
+		}
+
+		// -- Check
+		let current = fs::read_to_string(&path)?;
+		let rotated = fs::read_to_string(rotated_path(&path, 1))?;
+		fs::remove_dir_all(&dir)?;
+		assert_eq!(rotated.lines().count(), 2);
+		assert_eq!(current.lines().count(), 1);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
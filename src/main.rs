@@ -1,105 +1,137 @@
-#![allow(unused)] // For early development.
-
 // region:    --- Modules
 
+mod cli;
 mod clients;
 mod config;
+mod crypt;
 mod ctx;
+mod jobs;
 mod log;
+mod metrics;
 mod model;
+mod pwd;
+mod scheduler;
+mod shutdown;
 mod utils;
 mod web;
 
 pub use anyhow::{Error, Result};
 pub use config::config;
 
+use crate::cli::{Cli, Command, ConfigCommand};
 use crate::model::ModelManager;
-use crate::web::routes;
 
-use axum::response::Html;
-use axum::routing::get;
-use axum::{middleware, Router};
+use anyhow::Context;
+use clap::Parser;
 use clients::fixedfloat::client::FixedFloat;
-use std::net::SocketAddr;
-use tower_cookies::CookieManagerLayer;
-use tracing::info;
+use shutdown::Workers;
+use std::time::Duration;
+use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
 // endregion: --- Modules
 
+const UPSTREAM_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const WORKERS_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[tokio::main]
 async fn main() -> Result<()> {
 	tracing_subscriber::fmt()
 		.without_time() // For early local development.
 		.with_target(false)
 		.with_env_filter(EnvFilter::from_default_env())
+		.with_writer(log::RedactingMakeWriter)
 		.init();
 
-	let fixed_float = FixedFloat::new(
-		&config().FIXEDFLOAT_API_KEY,
-		&config().FIXEDFLOAT_API_SECRET,
-	)
-	.await;
-
-	let currencies = fixed_float.get_available_currencies().await.unwrap();
-	println!("{:?}", currencies);
-	panic!();
+	let cli = Cli::parse();
+
+	// -- Load the config, reporting every invalid key at once.
+	let config = match config::init(&cli.config) {
+		Ok(config) => config,
+		Err(config::Error::Invalid { errors }) => {
+			eprintln!("Invalid configuration:");
+			for error in errors {
+				eprintln!("  - {error}");
+			}
+			std::process::exit(2);
+		}
+		Err(ex) => {
+			eprintln!("Cannot load configuration: {ex}");
+			std::process::exit(2);
+		}
+	};
+
+	match cli.command.unwrap_or(Command::Serve) {
+		Command::Serve => serve().await,
+		Command::Config(ConfigCommand::Check) => {
+			// Secrets serialize as `[REDACTED]`.
+			print!("{}", serde_yaml::to_string(config)?);
+			Ok(())
+		}
+		command => cli::exec(command, cli.output).await,
+	}
+}
 
+async fn serve() -> Result<()> {
 	// Initialize ModelManager.
-	let mm = ModelManager::new().await?;
+	let mm = ModelManager::new()
+		.await
+		.context("Cannot connect to the database")?;
+
+	// Apply the pending schema migrations.
+	if config().database.migrate_on_startup {
+		let versions = mm.migrate().await.context("Cannot migrate the database")?;
+		info!(
+			"{:<12} - {} migration(s) applied",
+			"STARTUP",
+			versions.len()
+		);
+	}
+
+	// Background workers, stopped after the server has drained.
+	let mut workers = Workers::new();
 
-	let routes_all = Router::new()
-		.merge(routes::fixedfloat::routes(mm.clone()))
-		.merge(routes::utils::routes(mm.clone()))
-		// .layer(middleware::map_response(mw_reponse_map))
-		.fallback_service(routes::static_files::serve_dir());
+	// Initialize the request log sinks.
+	log::init_sinks(&mm, &mut workers)?;
+
+	// Check FixedFloat, without failing the startup.
+	if config().fixedfloat.check_on_startup {
+		check_upstream().await;
+	}
+
+	jobs::spawn(&mut workers, mm.clone());
+	scheduler::spawn(&mut workers, mm.clone())?;
+
+	let routes_all = web::routes_all(mm.clone());
 
 	// region:    --- Start Server
-	let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+	let addr = config().server.addr();
+	let server = axum::Server::try_bind(&addr)
+		.with_context(|| format!("Cannot bind to {addr}"))?;
 	info!("{:<12} - {addr}\n", "LISTENING");
-	axum::Server::bind(&addr)
+	server
 		.serve(routes_all.into_make_service())
-		.await
-		.unwrap();
+		.with_graceful_shutdown(shutdown::signal())
+		.await?;
 	// endregion: --- Start Server
 
+	workers.shutdown(WORKERS_SHUTDOWN_TIMEOUT).await;
+	info!("{:<12} - done", "SHUTDOWN");
+
 	Ok(())
 }
 
-// // Initialize the FixedFloat client
-// let fixed_float = FixedFloat::new(
-// 	config().FIXEDFLOAT_API_KEY.to_string(),
-// 	config().FIXEDFLOAT_API_SECRET.to_string(),
-// )
-// .await;
-
-// Get the available currencies
-// let currencies = fixed_float.get_available_currencies().await.unwrap();
-// println!("{:?}", currencies);
-
-// let exchange_rate_usdceth_to_btcln = fixed_float
-// 	.get_exchange_rate_usdceth_to_btcln("1.0".to_string())
-// 	.await
-// 	.unwrap();
-// println!(
-// 	"Exchange Rate USDCETH -> BTCLN {:?}",
-// 	exchange_rate_usdceth_to_btcln
-// );
-// let exchange_rate_btcln_to_usdceth = fixed_float
-// 	.get_exchange_rate_btcln_to_usdceth("1.0".to_string())
-// 	.await
-// 	.unwrap();
-// println!(
-// 	"Exchange Rate BTCLN -> USDCETH {:?}",
-// 	exchange_rate_btcln_to_usdceth
-// );
-
-// let create_order_result = fixed_float
-// 	.create_order_btcln_to_usdceth(
-// 		"0.001".to_string(),
-// 		"0x18241de74E1B36e80f91082ec1Ca1987f4e24d3b",
-// 	)
-// 	.await
-// 	.unwrap();
-
-// println!("Create Order Result {:?}", create_order_result);
+async fn check_upstream() {
+	let fixedfloat = FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await;
+
+	let check = fixedfloat.get_available_currencies_cached();
+	match tokio::time::timeout(UPSTREAM_CHECK_TIMEOUT, check).await {
+		Ok(Ok(_)) => info!("{:<12} - FixedFloat reachable", "STARTUP"),
+		Ok(Err(ex)) => warn!("FixedFloat check failed, starting anyway - {ex}"),
+		Err(_) => warn!("FixedFloat check timed out, starting anyway"),
+	}
+}
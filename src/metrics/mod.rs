@@ -0,0 +1,195 @@
+//! In-process Prometheus metrics, exposed in text format on `/metrics`.
+
+use crate::ctx::Ctx;
+use crate::model::order::OrderBmc;
+use crate::model::ModelManager;
+use anyhow::Result;
+use prometheus::{
+	Encoder, Gauge, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+	Registry, TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+struct Metrics {
+	registry: Registry,
+
+	// -- Http
+	http_requests_total: IntCounterVec,
+	http_request_duration_seconds: HistogramVec,
+
+	// -- Upstream FixedFloat
+	upstream_calls_total: IntCounterVec,
+	upstream_call_duration_seconds: HistogramVec,
+	upstream_error_codes_total: IntCounterVec,
+
+	// -- Orders
+	orders_created_total: IntCounterVec,
+	orders_by_status: IntGaugeVec,
+
+	// -- Cache
+	cache_requests_total: IntCounterVec,
+
+	// -- Poller
+	order_poller_lag_seconds: Gauge,
+}
+
+fn metrics() -> &'static Metrics {
+	static INSTANCE: OnceLock<Metrics> = OnceLock::new();
+
+	INSTANCE.get_or_init(|| {
+		Metrics::new().unwrap_or_else(|ex| {
+			panic!("FATAL - WHILE REGISTERING METRICS - Cause: {ex:?}")
+		})
+	})
+}
+
+impl Metrics {
+	fn new() -> Result<Self> {
+		let registry = Registry::new();
+
+		let http_requests_total = IntCounterVec::new(
+			Opts::new("http_requests_total", "HTTP requests by route and status."),
+			&["method", "route", "status"],
+		)?;
+		let http_request_duration_seconds = HistogramVec::new(
+			HistogramOpts::new(
+				"http_request_duration_seconds",
+				"HTTP request latency by route.",
+			),
+			&["method", "route"],
+		)?;
+
+		let upstream_calls_total = IntCounterVec::new(
+			Opts::new(
+				"upstream_calls_total",
+				"FixedFloat API calls by method and result.",
+			),
+			&["method", "result"],
+		)?;
+		let upstream_call_duration_seconds = HistogramVec::new(
+			HistogramOpts::new(
+				"upstream_call_duration_seconds",
+				"FixedFloat API call latency by method.",
+			),
+			&["method"],
+		)?;
+		let upstream_error_codes_total = IntCounterVec::new(
+			Opts::new(
+				"upstream_error_codes_total",
+				"FixedFloat non zero response codes by method.",
+			),
+			&["method", "code"],
+		)?;
+
+		let orders_created_total = IntCounterVec::new(
+			Opts::new("orders_created_total", "Orders created by pair and type."),
+			&["pair", "type"],
+		)?;
+		let orders_by_status = IntGaugeVec::new(
+			Opts::new("orders_by_status", "Orders by current status."),
+			&["status"],
+		)?;
+
+		let cache_requests_total = IntCounterVec::new(
+			Opts::new("cache_requests_total", "Cache lookups by cache and result."),
+			&["cache", "result"],
+		)?;
+
+		let order_poller_lag_seconds = Gauge::new(
+			"order_poller_lag_seconds",
+			"Age of the oldest pending order status refresh.",
+		)?;
+
+		registry.register(Box::new(http_requests_total.clone()))?;
+		registry.register(Box::new(http_request_duration_seconds.clone()))?;
+		registry.register(Box::new(upstream_calls_total.clone()))?;
+		registry.register(Box::new(upstream_call_duration_seconds.clone()))?;
+		registry.register(Box::new(upstream_error_codes_total.clone()))?;
+		registry.register(Box::new(orders_created_total.clone()))?;
+		registry.register(Box::new(orders_by_status.clone()))?;
+		registry.register(Box::new(cache_requests_total.clone()))?;
+		registry.register(Box::new(order_poller_lag_seconds.clone()))?;
+
+		Ok(Metrics {
+			registry,
+			http_requests_total,
+			http_request_duration_seconds,
+			upstream_calls_total,
+			upstream_call_duration_seconds,
+			upstream_error_codes_total,
+			orders_created_total,
+			orders_by_status,
+			cache_requests_total,
+			order_poller_lag_seconds,
+		})
+	}
+}
+
+// region:    --- Recorders
+pub fn observe_http(method: &str, route: &str, status: u16, elapsed: Duration) {
+	let m = metrics();
+	m.http_requests_total
+		.with_label_values(&[method, route, &status.to_string()])
+		.inc();
+	m.http_request_duration_seconds
+		.with_label_values(&[method, route])
+		.observe(elapsed.as_secs_f64());
+}
+
+/// `result` is `ok`, `rejected`, or the failure kind.
+pub fn observe_upstream(
+	method: &str,
+	result: &str,
+	code: Option<i32>,
+	elapsed: Duration,
+) {
+	let m = metrics();
+	m.upstream_calls_total
+		.with_label_values(&[method, result])
+		.inc();
+	m.upstream_call_duration_seconds
+		.with_label_values(&[method])
+		.observe(elapsed.as_secs_f64());
+	if let Some(code) = code.filter(|c| *c != 0) {
+		m.upstream_error_codes_total
+			.with_label_values(&[method, &code.to_string()])
+			.inc();
+	}
+}
+
+pub fn order_created(from_ccy: &str, to_ccy: &str, order_type: &str) {
+	metrics()
+		.orders_created_total
+		.with_label_values(&[&format!("{from_ccy}/{to_ccy}"), order_type])
+		.inc();
+}
+
+pub fn cache_access(cache: &str, hit: bool) {
+	let result = if hit { "hit" } else { "miss" };
+	metrics()
+		.cache_requests_total
+		.with_label_values(&[cache, result])
+		.inc();
+}
+
+pub fn set_order_poller_lag(lag: Duration) {
+	metrics().order_poller_lag_seconds.set(lag.as_secs_f64());
+}
+// endregion: --- Recorders
+
+/// Refresh the db backed gauges, and encode all metrics in text format.
+pub async fn render(mm: &ModelManager) -> Result<String> {
+	let m = metrics();
+
+	let counts = OrderBmc::count_by_status(&Ctx::root_ctx(), mm).await?;
+	m.orders_by_status.reset();
+	for (status, count) in counts {
+		m.orders_by_status.with_label_values(&[&status]).set(count);
+	}
+
+	let mut buffer = Vec::new();
+	TextEncoder::new().encode(&m.registry.gather(), &mut buffer)?;
+
+	Ok(String::from_utf8(buffer)?)
+}
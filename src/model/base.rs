@@ -1,110 +1,349 @@
 use crate::ctx::Ctx;
-use crate::model::ModelManager;
-use anyhow::{anyhow, Error, Result};
+use crate::model::filter::{
+	push_filter, push_list_options, FieldKind, Filter, FilterFields, ListOptions,
+};
+use crate::model::store::{Bind, FromDbRow, Sql};
+use crate::model::{Error, ModelManager, Result};
+use crate::utils::now_utc;
 use sqlb::HasFields;
-use sqlx::postgres::PgRow;
-use sqlx::FromRow;
 
+/// The table level settings of a BMC (Backend Model Controller).
+///
+/// INVARIANT: A BMC function must not make another model call while it
+///            holds the connection from `mm.db()`. On a transactional
+///            manager, it is the transaction one, locked until dropped, so
+///            the nested call would wait on it forever. Get it last, and
+///            drop it before any other model call.
 pub trait DbBmc {
 	const TABLE: &'static str;
+
+	/// When true, rows carry an `owner_id` set from the `Ctx` on create,
+	/// and are only visible to that user (the root ctx sees all rows).
+	const OWNER_SCOPED: bool = false;
+
+	/// When true, rows carry a `version` column, and updates go through
+	/// `update_versioned`, which fails on a stale version.
+	const VERSIONED: bool = false;
+
+	/// Kinds of the columns that are neither TEXT nor common (`id`,
+	/// `owner_id`, `version` and the audit columns), which the list
+	/// filter values are checked against.
+	const FIELD_KINDS: &'static [(&'static str, FieldKind)] = &[];
+}
+
+// region:    --- DataFields
+/// The column values of a create or update, the `None` ones skipped.
+pub trait DataFields {
+	fn data_fields(self) -> Vec<(&'static str, Bind)>;
+}
+
+/// Implement `DataFields` for a struct, from its field names.
+/// e.g., `impl_data_fields!(OrderForUpdate { status });`
+macro_rules! impl_data_fields {
+	($struct:ty { $($field:ident),* $(,)? }) => {
+		impl $crate::model::base::DataFields for $struct {
+			fn data_fields(
+				self,
+			) -> Vec<(&'static str, $crate::model::store::Bind)> {
+				let mut fields = Vec::new();
+				$(
+					let val = $crate::model::store::IntoBind::into_bind(self.$field);
+					if let Some(val) = val {
+						fields.push((stringify!($field), val));
+					}
+				)*
+				fields
+			}
+		}
+	};
+}
+pub(crate) use impl_data_fields;
+// endregion: --- DataFields
+
+/// Set `cid`, `ctime`, `mid` and `mtime` from the ctx user and now.
+fn add_timestamps_for_create(fields: &mut Vec<(&'static str, Bind)>, user_id: i64) {
+	let now = now_utc();
+	fields.push(("cid", user_id.into()));
+	fields.push(("ctime", now.into()));
+	fields.push(("mid", user_id.into()));
+	fields.push(("mtime", now.into()));
+}
+
+/// Set `mid` and `mtime` from the ctx user and now.
+pub(in crate::model) fn add_timestamps_for_update(
+	fields: &mut Vec<(&'static str, Bind)>,
+	user_id: i64,
+) {
+	fields.push(("mid", user_id.into()));
+	fields.push(("mtime", now_utc().into()));
 }
 
-pub async fn create<MC, E>(_ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
+/// Returns the owner id the queries of this entity must be scoped by, if any.
+pub fn owner_id<MC>(ctx: &Ctx) -> Option<i64>
 where
 	MC: DbBmc,
-	E: HasFields,
 {
-	let db = mm.db();
+	if MC::OWNER_SCOPED && ctx.user_id() != Ctx::root_ctx().user_id() {
+		Some(ctx.user_id())
+	} else {
+		None
+	}
+}
+
+pub async fn create<MC, E>(ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
+where
+	MC: DbBmc,
+	E: DataFields,
+{
+	let mut fields = data.data_fields();
+	if MC::OWNER_SCOPED {
+		fields.push(("owner_id", ctx.user_id().into()));
+	}
+	add_timestamps_for_create(&mut fields, ctx.user_id());
 
-	let fields = data.not_none_fields();
-	let (id,) = sqlb::insert()
-		.table(MC::TABLE)
-		.data(fields)
-		.returning(&["id"])
-		.fetch_one::<_, (i64,)>(db)
-		.await?;
+	let columns = quoted_columns(fields.iter().map(|(name, _)| *name));
+	let mut sql = Sql::new(
+		mm.dialect(),
+		format!("INSERT INTO \"{}\" ({columns}) VALUES (", MC::TABLE),
+	);
+	for (i, (_, val)) in fields.into_iter().enumerate() {
+		if i > 0 {
+			sql.push(", ");
+		}
+		sql.push_bind(val);
+	}
+	sql.push(") RETURNING id");
+
+	let mut db = mm.db().await?;
+	let (id,) = sql.fetch_one::<(i64,)>(&mut db).await?;
 
 	Ok(id)
 }
 
-pub async fn get<MC, E>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
+pub async fn get<MC, E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
 where
 	MC: DbBmc,
-	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
-	E: HasFields,
+	E: FromDbRow + HasFields,
 {
-	let db = mm.db();
+	let mut sql = select::<MC, E>(mm, ctx, None)?;
+	sql.push(" AND id = ").push_bind(id);
 
-	let entity: E = sqlb::select()
-		.table(MC::TABLE)
-		.columns(E::field_names())
-		.and_where("id", "=", id)
-		.fetch_optional(db)
-		.await?
-		.ok_or(anyhow!("Entity not found"))?;
+	let mut db = mm.db().await?;
+	let entity: E =
+		sql.fetch_optional(&mut db)
+			.await?
+			.ok_or(Error::EntityNotFound {
+				entity: MC::TABLE,
+				id,
+			})?;
 
 	Ok(entity)
 }
 
-pub async fn list<MC, E>(_ctx: &Ctx, mm: &ModelManager) -> Result<Vec<E>>
+pub async fn list<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<Filter>,
+	list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
 where
 	MC: DbBmc,
-	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
-	E: HasFields,
+	E: FromDbRow + HasFields,
 {
-	let db = mm.db();
+	let mut sql = select::<MC, E>(mm, ctx, filter.as_ref())?;
+	push_list_options(
+		&mut sql,
+		MC::TABLE,
+		owner_id::<MC>(ctx),
+		&list_options.unwrap_or_default(),
+		filter_fields::<MC, E>(),
+	)?;
 
-	let entities: Vec<E> = sqlb::select()
-		.table(MC::TABLE)
-		.columns(E::field_names())
-		.order_by("id")
-		.fetch_all(db)
-		.await?;
+	let mut db = mm.db().await?;
+	let entities: Vec<E> = sql.fetch_all(&mut db).await?;
 
 	Ok(entities)
 }
 
-pub async fn update<MC, E>(
-	_ctx: &Ctx,
+/// Number of rows matching the filter, visible from the `ctx`.
+/// Fields are checked against `E` as in `list`.
+pub async fn count<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<Filter>,
+) -> Result<i64>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
+	let mut sql = Sql::new(mm.dialect(), "SELECT count(*) FROM ");
+	push_where::<MC>(&mut sql, ctx, filter.as_ref(), filter_fields::<MC, E>())?;
+
+	let mut db = mm.db().await?;
+	let (count,) = sql.fetch_one::<(i64,)>(&mut db).await?;
+
+	Ok(count)
+}
+
+/// `SELECT` of the `E` fields, with the where clause of `push_where`.
+pub(in crate::model) fn select<MC, E>(
+	mm: &ModelManager,
+	ctx: &Ctx,
+	filter: Option<&Filter>,
+) -> Result<Sql>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
+	let columns = quoted_columns(E::field_names().iter().copied());
+	let mut sql = Sql::new(mm.dialect(), format!("SELECT {columns} FROM "));
+	push_where::<MC>(&mut sql, ctx, filter, filter_fields::<MC, E>())?;
+
+	Ok(sql)
+}
+
+fn filter_fields<MC, E>() -> FilterFields
+where
+	MC: DbBmc,
+	E: HasFields,
+{
+	FilterFields {
+		names: E::field_names(),
+		kinds: MC::FIELD_KINDS,
+	}
+}
+
+/// Push the table and the where clause: owner scope and filter.
+fn push_where<MC>(
+	sql: &mut Sql,
+	ctx: &Ctx,
+	filter: Option<&Filter>,
+	fields: FilterFields,
+) -> Result<()>
+where
+	MC: DbBmc,
+{
+	sql.push(&format!("\"{}\" WHERE TRUE", MC::TABLE));
+	if let Some(owner_id) = owner_id::<MC>(ctx) {
+		sql.push(" AND owner_id = ").push_bind(owner_id);
+	}
+	if let Some(filter) = filter {
+		push_filter(sql, filter, fields)?;
+	}
+
+	Ok(())
+}
+
+fn quoted_columns<'a>(names: impl Iterator<Item = &'a str>) -> String {
+	names
+		.map(|name| format!("\"{name}\""))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+/// `UPDATE` of the row `id` (owner scoped) with the fields.
+pub(in crate::model) fn update_sql<MC>(
 	mm: &ModelManager,
+	ctx: &Ctx,
 	id: i64,
+	fields: Vec<(&'static str, Bind)>,
+) -> Sql
+where
+	MC: DbBmc,
+{
+	let mut sql = Sql::new(mm.dialect(), format!("UPDATE \"{}\" SET ", MC::TABLE));
+	for (i, (name, val)) in fields.into_iter().enumerate() {
+		if i > 0 {
+			sql.push(", ");
+		}
+		sql.push(&format!("\"{name}\" = ")).push_bind(val);
+	}
+	sql.push(" WHERE id = ").push_bind(id);
+	if let Some(owner_id) = owner_id::<MC>(ctx) {
+		sql.push(" AND owner_id = ").push_bind(owner_id);
+	}
+
+	sql
+}
+
+/// Update of a `VERSIONED` entity, only if its row is still at `version`.
+/// Returns the new version.
+///
+/// Fails with `VersionConflict` when the row was updated since `version`
+/// was read, in which case the caller should re-read it and retry.
+pub async fn update_versioned<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	version: i64,
 	data: E,
-) -> Result<()>
+) -> Result<i64>
 where
 	MC: DbBmc,
-	E: HasFields,
+	E: DataFields,
 {
-	let db = mm.db();
+	if !MC::VERSIONED {
+		return Err(Error::VersionNotSupported { entity: MC::TABLE });
+	}
 
-	let fields = data.not_none_fields();
-	let count = sqlb::update()
-		.table(MC::TABLE)
-		.and_where("id", "=", id)
-		.data(fields)
-		.exec(db)
-		.await?;
+	let mut fields = data.data_fields();
+	add_timestamps_for_update(&mut fields, ctx.user_id());
+	fields.push(("version", (version + 1).into()));
+	let mut sql = update_sql::<MC>(mm, ctx, id, fields);
+	sql.push(" AND version = ").push_bind(version);
 
-	if count == 0 {
-		Err(anyhow!("Entity not found"))
-	} else {
-		Ok(())
+	let mut db = mm.db().await?;
+	let count = sql.execute(&mut db).await?;
+
+	if count > 0 {
+		return Ok(version + 1);
+	}
+
+	// -- Tell a missing row from a stale version.
+	let mut sql = Sql::new(
+		mm.dialect(),
+		format!("SELECT version FROM \"{}\" WHERE id = ", MC::TABLE),
+	);
+	sql.push_bind(id);
+	if let Some(owner_id) = owner_id::<MC>(ctx) {
+		sql.push(" AND owner_id = ").push_bind(owner_id);
+	}
+
+	match sql.fetch_optional::<(i64,)>(&mut db).await? {
+		Some((current,)) => Err(Error::VersionConflict {
+			entity: MC::TABLE,
+			id,
+			version,
+			current,
+		}),
+		None => Err(Error::EntityNotFound {
+			entity: MC::TABLE,
+			id,
+		}),
 	}
 }
 
-pub async fn delete<MC>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+pub async fn delete<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
 where
 	MC: DbBmc,
 {
-	let db = mm.db();
+	let mut sql = Sql::new(
+		mm.dialect(),
+		format!("DELETE FROM \"{}\" WHERE id = ", MC::TABLE),
+	);
+	sql.push_bind(id);
+	if let Some(owner_id) = owner_id::<MC>(ctx) {
+		sql.push(" AND owner_id = ").push_bind(owner_id);
+	}
 
-	let count = sqlb::delete()
-		.table(MC::TABLE)
-		.and_where("id", "=", id)
-		.exec(db)
-		.await?;
+	let mut db = mm.db().await?;
+	let count = sql.execute(&mut db).await?;
 
 	if count == 0 {
-		Err(anyhow!("Entity not found"))
+		Err(Error::EntityNotFound {
+			entity: MC::TABLE,
+			id,
+		})
 	} else {
 		Ok(())
 	}
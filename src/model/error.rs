@@ -0,0 +1,123 @@
+use crate::model::store;
+use crate::pwd;
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+use sqlx::postgres::PgDatabaseError;
+use sqlx::sqlite::SqliteError;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub enum Error {
+	EntityNotFound {
+		entity: &'static str,
+		id: i64,
+	},
+	UniqueViolation {
+		table: String,
+		constraint: String,
+	},
+
+	// -- Versioned updates
+	/// The row is at `current`, not at the `version` the update was based on.
+	VersionConflict {
+		entity: &'static str,
+		id: i64,
+		version: i64,
+		current: i64,
+	},
+	VersionNotSupported {
+		entity: &'static str,
+	},
+
+	// -- List
+	ListFieldUnknown {
+		field: String,
+	},
+	ListValueInvalid {
+		field: String,
+		value: String,
+		expected: &'static str,
+	},
+
+	// -- User
+	UserHasNoPwd {
+		user_id: i64,
+	},
+
+	// -- Job
+	/// Only a dead (or pending) job can be retried.
+	JobNotRetryable {
+		id: i64,
+		status: String,
+	},
+
+	// -- Modules
+	Pwd(pwd::Error),
+	Store(store::Error),
+
+	// -- Externals
+	Sqlx(#[serde_as(as = "DisplayFromStr")] sqlx::Error),
+}
+
+// region:    --- Froms
+impl From<pwd::Error> for Error {
+	fn from(val: pwd::Error) -> Self {
+		Self::Pwd(val)
+	}
+}
+
+impl From<store::Error> for Error {
+	fn from(val: store::Error) -> Self {
+		Self::Store(val)
+	}
+}
+
+/// Unique constraint violations (Postgres 23505, SQLite 2067 or 1555)
+/// get their own variant.
+impl From<sqlx::Error> for Error {
+	fn from(val: sqlx::Error) -> Self {
+		if let sqlx::Error::Database(db_err) = &val {
+			if let Some(pg_err) = db_err.try_downcast_ref::<PgDatabaseError>() {
+				if pg_err.code() == "23505" {
+					return Self::UniqueViolation {
+						table: pg_err.table().unwrap_or_default().to_string(),
+						constraint: pg_err
+							.constraint()
+							.unwrap_or_default()
+							.to_string(),
+					};
+				}
+			}
+
+			// e.g., "UNIQUE constraint failed: user.username"
+			let sqlite_unique = db_err.try_downcast_ref::<SqliteError>().is_some()
+				&& matches!(db_err.code().as_deref(), Some("2067" | "1555"));
+			if sqlite_unique {
+				let message = db_err.message();
+				let columns = message.rsplit(": ").next().unwrap_or_default();
+				return Self::UniqueViolation {
+					table: columns.split('.').next().unwrap_or_default().to_string(),
+					constraint: columns.to_string(),
+				};
+			}
+		}
+
+		Self::Sqlx(val)
+	}
+}
+// endregion: --- Froms
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
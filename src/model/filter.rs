@@ -0,0 +1,464 @@
+//! Filters and list options of `base::list` and `base::count`,
+//! deserializable from the JSON of the REST list endpoints.
+//!
+//! e.g., `{"or": [{"status": {"$in": ["NEW", "PENDING"]}},
+//!               {"to_ccy": {"$eq": "BTCLN"}, "id": {"$gt": 1200}}]}`
+
+use crate::model::store::{Bind, Sql};
+use crate::model::{Dialect, Error, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+pub const LIST_LIMIT_DEFAULT: i64 = 300;
+pub const LIST_LIMIT_MAX: i64 = 1000;
+
+// region:    --- Filter
+/// The conditions of a field map are AND-ed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Filter {
+	And { and: Vec<Filter> },
+	Or { or: Vec<Filter> },
+	Fields(BTreeMap<String, FieldOps>),
+}
+
+/// The operators of one field, AND-ed.
+///
+/// Values must be of the field kind: an integer, a number, a bool,
+/// a string, or an RFC3339 string for a timestamp.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldOps {
+	#[serde(rename = "$eq")]
+	pub eq: Option<Value>,
+	#[serde(rename = "$ne")]
+	pub ne: Option<Value>,
+	#[serde(rename = "$lt")]
+	pub lt: Option<Value>,
+	#[serde(rename = "$lte")]
+	pub lte: Option<Value>,
+	#[serde(rename = "$gt")]
+	pub gt: Option<Value>,
+	#[serde(rename = "$gte")]
+	pub gte: Option<Value>,
+	#[serde(rename = "$in")]
+	pub in_: Option<Vec<Value>>,
+	/// Substring match, case sensitive.
+	#[serde(rename = "$contains")]
+	pub contains: Option<String>,
+	/// `true` for IS NULL, `false` for IS NOT NULL.
+	#[serde(rename = "$null")]
+	pub null: Option<bool>,
+}
+
+impl Filter {
+	/// Convenience for the common single field equality.
+	pub fn eq(field: &str, val: impl Into<Value>) -> Self {
+		let ops = FieldOps {
+			eq: Some(val.into()),
+			..Default::default()
+		};
+
+		Filter::Fields(BTreeMap::from([(field.to_string(), ops)]))
+	}
+
+	/// Convenience for the single field inequality.
+	pub fn ne(field: &str, val: impl Into<Value>) -> Self {
+		let ops = FieldOps {
+			ne: Some(val.into()),
+			..Default::default()
+		};
+
+		Filter::Fields(BTreeMap::from([(field.to_string(), ops)]))
+	}
+}
+// endregion: --- Filter
+
+// region:    --- ListOptions
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListOptions {
+	pub limit: Option<i64>,
+	pub offset: Option<i64>,
+	/// Id of the last row of the previous page. Rows after it, in the
+	/// `order_bys` order, are returned.
+	pub cursor: Option<i64>,
+	/// Field names, prefixed with `!` for descending. `id` is always
+	/// added last, as tie breaker.
+	pub order_bys: Option<Vec<String>>,
+}
+// endregion: --- ListOptions
+
+// region:    --- FilterFields
+/// Kind of a column, which the filter values must match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+	Int,
+	Text,
+	Time,
+}
+
+impl FieldKind {
+	fn name(self) -> &'static str {
+		match self {
+			FieldKind::Int => "integer",
+			FieldKind::Text => "string",
+			FieldKind::Time => "RFC3339 timestamp",
+		}
+	}
+}
+
+/// Kinds of the columns common to the entities.
+const COMMON_FIELD_KINDS: &[(&str, FieldKind)] = &[
+	("id", FieldKind::Int),
+	("owner_id", FieldKind::Int),
+	("version", FieldKind::Int),
+	("cid", FieldKind::Int),
+	("ctime", FieldKind::Time),
+	("mid", FieldKind::Int),
+	("mtime", FieldKind::Time),
+];
+
+/// The fields the filters and list options can refer to.
+#[derive(Clone, Copy)]
+pub(in crate::model) struct FilterFields {
+	pub names: &'static [&'static str],
+	/// Kinds of the fields neither TEXT nor common (see `DbBmc`).
+	pub kinds: &'static [(&'static str, FieldKind)],
+}
+
+impl FilterFields {
+	/// The quoted column of a field, which must be one of the `names`.
+	fn column(&self, field: &str) -> Result<String> {
+		if !self.names.contains(&field) {
+			return Err(Error::ListFieldUnknown {
+				field: field.to_string(),
+			});
+		}
+
+		Ok(format!("\"{field}\""))
+	}
+
+	fn kind(&self, field: &str) -> FieldKind {
+		self.kinds
+			.iter()
+			.chain(COMMON_FIELD_KINDS)
+			.find(|(name, _)| *name == field)
+			.map(|(_, kind)| *kind)
+			.unwrap_or(FieldKind::Text)
+	}
+}
+// endregion: --- FilterFields
+
+// region:    --- Sql Builders
+/// Push ` AND (...)` for the filter. Fields are checked against `fields`.
+pub(in crate::model) fn push_filter(
+	sql: &mut Sql,
+	filter: &Filter,
+	fields: FilterFields,
+) -> Result<()> {
+	sql.push(" AND ");
+	push_filter_node(sql, filter, fields)
+}
+
+fn push_filter_node(
+	sql: &mut Sql,
+	filter: &Filter,
+	fields: FilterFields,
+) -> Result<()> {
+	let (nodes, join, empty) = match filter {
+		Filter::And { and } => (and, " AND ", "TRUE"),
+		Filter::Or { or } => (or, " OR ", "FALSE"),
+		Filter::Fields(field_ops) => return push_fields(sql, field_ops, fields),
+	};
+
+	if nodes.is_empty() {
+		sql.push(empty);
+		return Ok(());
+	}
+
+	sql.push("(");
+	for (i, node) in nodes.iter().enumerate() {
+		if i > 0 {
+			sql.push(join);
+		}
+		push_filter_node(sql, node, fields)?;
+	}
+	sql.push(")");
+
+	Ok(())
+}
+
+fn push_fields(
+	sql: &mut Sql,
+	field_ops: &BTreeMap<String, FieldOps>,
+	fields: FilterFields,
+) -> Result<()> {
+	sql.push("(TRUE");
+
+	for (field, ops) in field_ops {
+		let col = fields.column(field)?;
+		let kind = fields.kind(field);
+
+		let comparisons = [
+			("=", &ops.eq),
+			("<>", &ops.ne),
+			("<", &ops.lt),
+			("<=", &ops.lte),
+			(">", &ops.gt),
+			(">=", &ops.gte),
+		];
+		for (op, val) in comparisons {
+			if let Some(val) = val {
+				sql.push(&format!(" AND {col} {op} "));
+				push_value(sql, field, kind, val)?;
+			}
+		}
+
+		if let Some(vals) = &ops.in_ {
+			if vals.is_empty() {
+				sql.push(" AND FALSE");
+			} else {
+				sql.push(&format!(" AND {col} IN ("));
+				for (i, val) in vals.iter().enumerate() {
+					if i > 0 {
+						sql.push(", ");
+					}
+					push_value(sql, field, kind, val)?;
+				}
+				sql.push(")");
+			}
+		}
+
+		if let Some(contains) = &ops.contains {
+			if kind != FieldKind::Text {
+				return Err(value_invalid(field, kind, contains));
+			}
+			let position_fn = match sql.dialect() {
+				Dialect::Postgres => "strpos",
+				Dialect::Sqlite => "instr",
+			};
+			sql.push(&format!(" AND {position_fn}({col}, "));
+			sql.push_bind(contains.to_string());
+			sql.push(") > 0");
+		}
+
+		if let Some(null) = ops.null {
+			let not = if null { "" } else { "NOT " };
+			sql.push(&format!(" AND {col} IS {not}NULL"));
+		}
+	}
+
+	sql.push(")");
+
+	Ok(())
+}
+
+fn push_value(
+	sql: &mut Sql,
+	field: &str,
+	kind: FieldKind,
+	val: &Value,
+) -> Result<()> {
+	let bind: Bind = match (kind, val) {
+		(FieldKind::Int, Value::Number(num)) => num.as_i64().map(Bind::from),
+		(FieldKind::Text, Value::String(val)) => Some(val.as_str().into()),
+		(FieldKind::Time, Value::String(val)) => {
+			OffsetDateTime::parse(val, &Rfc3339).ok().map(Bind::from)
+		}
+		_ => None,
+	}
+	.ok_or_else(|| value_invalid(field, kind, val))?;
+	sql.push_bind(bind);
+
+	Ok(())
+}
+
+fn value_invalid(field: &str, kind: FieldKind, val: impl ToString) -> Error {
+	Error::ListValueInvalid {
+		field: field.to_string(),
+		value: val.to_string(),
+		expected: kind.name(),
+	}
+}
+
+/// Push ` ORDER BY ... LIMIT ... OFFSET ...`, and the cursor condition
+/// (to be pushed right after the filters).
+///
+/// The cursor row is read with the `owner_id` scope, so a row of another
+/// owner matches nothing.
+pub(in crate::model) fn push_list_options(
+	sql: &mut Sql,
+	table: &str,
+	owner_id: Option<i64>,
+	options: &ListOptions,
+	fields: FilterFields,
+) -> Result<()> {
+	// -- Order bys, with `id` as tie breaker.
+	let mut order_bys: Vec<(String, bool)> = Vec::new();
+	for order_by in options.order_bys.iter().flatten() {
+		let (field, desc) = match order_by.strip_prefix('!') {
+			Some(field) => (field, true),
+			None => (order_by.as_str(), false),
+		};
+		order_bys.push((fields.column(field)?, desc));
+	}
+	if !order_bys.iter().any(|(col, _)| col == "\"id\"") {
+		order_bys.push(("\"id\"".to_string(), false));
+	}
+
+	// -- Cursor: (f1 after c1) OR (f1 = c1 AND f2 after c2) OR ...
+	//    with ci the values of the cursor row.
+	if let Some(cursor) = options.cursor {
+		let push_cursor_val = |sql: &mut Sql, col: &str| {
+			sql.push(&format!("(SELECT {col} FROM \"{table}\" WHERE id = "));
+			sql.push_bind(cursor);
+			if let Some(owner_id) = owner_id {
+				sql.push(" AND owner_id = ").push_bind(owner_id);
+			}
+			sql.push(")");
+		};
+
+		sql.push(" AND (FALSE");
+		for (i, (col, desc)) in order_bys.iter().enumerate() {
+			sql.push(" OR (TRUE");
+			for (prev_col, _) in &order_bys[..i] {
+				sql.push(&format!(" AND {prev_col} = "));
+				push_cursor_val(sql, prev_col);
+			}
+			let op = if *desc { "<" } else { ">" };
+			sql.push(&format!(" AND {col} {op} "));
+			push_cursor_val(sql, col);
+			sql.push(")");
+		}
+		sql.push(")");
+	}
+
+	sql.push(" ORDER BY ");
+	for (i, (col, desc)) in order_bys.iter().enumerate() {
+		if i > 0 {
+			sql.push(", ");
+		}
+		sql.push(col);
+		if *desc {
+			sql.push(" DESC");
+		}
+	}
+
+	let limit = options
+		.limit
+		.unwrap_or(LIST_LIMIT_DEFAULT)
+		.clamp(1, LIST_LIMIT_MAX);
+	sql.push(" LIMIT ");
+	sql.push_bind(limit);
+
+	if let Some(offset) = options.offset.filter(|o| *o > 0) {
+		sql.push(" OFFSET ");
+		sql.push_bind(offset);
+	}
+
+	Ok(())
+}
+
+// endregion: --- Sql Builders
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use serde_json::json;
+
+	const FX_FIELDS: FilterFields = FilterFields {
+		names: &["id", "status", "ctime"],
+		kinds: &[],
+	};
+
+	fn fx_filter(filter: Value) -> Result<Filter> {
+		Ok(serde_json::from_value(filter)?)
+	}
+
+	#[test]
+	fn test_push_filter_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let filter = fx_filter(json!({"or": [
+			{"status": {"$in": ["NEW", "PENDING"]}},
+			{"id": {"$gt": 1200}, "ctime": {"$gte": "2026-10-19T10:00:00Z"}},
+		]}))?;
+		let mut sql = Sql::new(Dialect::Postgres, "");
+
+		// -- Exec
+		push_filter(&mut sql, &filter, FX_FIELDS)?;
+
+		// -- Check
+		assert_eq!(
+			sql.sql(),
+			r#" AND ((TRUE AND "status" IN ($1, $2)) OR (TRUE AND "ctime" >= $3 AND "id" > $4))"#
+		);
+		assert!(matches!(
+			sql.binds(),
+			[Bind::Text(_), Bind::Text(_), Bind::Time(_), Bind::I64(1200)]
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_push_filter_err_value_invalid() -> Result<()> {
+		let fx_filters = [
+			json!({"status": {"$eq": 5}}),
+			json!({"id": {"$eq": "12"}}),
+			json!({"id": {"$gt": 1.5}}),
+			json!({"id": {"$contains": "1"}}),
+			json!({"ctime": {"$gt": "yesterday"}}),
+			json!({"id": {"$in": [1, "2"]}}),
+		];
+
+		for fx_filter_json in fx_filters {
+			// -- Exec
+			let mut sql = Sql::new(Dialect::Postgres, "");
+			let res = push_filter(
+				&mut sql,
+				&fx_filter(fx_filter_json.clone())?,
+				FX_FIELDS,
+			);
+
+			// -- Check
+			assert!(
+				matches!(res, Err(Error::ListValueInvalid { .. })),
+				"{fx_filter_json} should be ListValueInvalid, was {res:?}"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_push_list_options_cursor_owner_scoped() -> Result<()> {
+		// -- Setup & Fixtures
+		let options = ListOptions {
+			cursor: Some(7),
+			limit: Some(10),
+			..Default::default()
+		};
+		let mut sql = Sql::new(Dialect::Postgres, "");
+
+		// -- Exec
+		push_list_options(&mut sql, "order", Some(3), &options, FX_FIELDS)?;
+
+		// -- Check
+		assert_eq!(
+			sql.sql(),
+			r#" AND (FALSE OR (TRUE AND "id" > (SELECT "id" FROM "order" WHERE id = $1 AND owner_id = $2))) ORDER BY "id" LIMIT $3"#
+		);
+		assert!(matches!(
+			sql.binds(),
+			[Bind::I64(7), Bind::I64(3), Bind::I64(10)]
+		));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
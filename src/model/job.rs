@@ -0,0 +1,480 @@
+//! Durable job queue. Workers claim due jobs with a lease (`FOR UPDATE
+//! SKIP LOCKED` on Postgres), so several server instances can share it,
+//! and a job left by a crashed worker is taken over once its lease expires.
+//!
+//! Run by the `jobs` worker runtime. Its updates are made as root (`mid` 0).
+
+use crate::ctx::Ctx;
+use crate::model::store::{first_row, on_conn, Dialect, Sql};
+use crate::model::{Error, ModelManager, Result};
+use crate::utils::now_utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::FromRow;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const JOB_COLUMNS: &str = "id, kind, payload, status, run_at, attempts, \
+	max_attempts, last_error, locked_by, locked_until, cid, ctime, mid, mtime";
+
+/// The modifier of the worker updates.
+const ROOT_ID: i64 = 0;
+
+// region:    --- Job Types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, strum_macros::AsRefStr)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum JobStatus {
+	Pending,
+	Running,
+	Done,
+	/// Failed `max_attempts` times, waiting for an admin retry.
+	Dead,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Job {
+	pub id: i64,
+	pub kind: String,
+	pub payload: Json<Value>,
+	pub status: String,
+
+	// -- Scheduling
+	#[serde(with = "time::serde::rfc3339")]
+	pub run_at: OffsetDateTime,
+	pub attempts: i32,
+	pub max_attempts: i32,
+	pub last_error: Option<String>,
+
+	// -- Lease
+	pub locked_by: Option<String>,
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub locked_until: Option<OffsetDateTime>,
+
+	// -- Timestamps
+	//    (creator and last modifier user_id/time)
+	pub cid: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+pub struct JobForCreate {
+	pub kind: String,
+	pub payload: Value,
+	/// Now when `None`.
+	pub run_at: Option<OffsetDateTime>,
+	/// `JobBmc::DEFAULT_MAX_ATTEMPTS` when `None`.
+	pub max_attempts: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct JobFilter {
+	pub status: Option<JobStatus>,
+	pub kind: Option<String>,
+	pub limit: Option<i64>,
+}
+// endregion: --- Job Types
+
+// region:    --- JobBmc
+pub struct JobBmc;
+
+impl JobBmc {
+	pub const DEFAULT_MAX_ATTEMPTS: i32 = 10;
+	const DEFAULT_LIMIT: i64 = 100;
+	const MAX_LIMIT: i64 = 1000;
+
+	pub async fn enqueue(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		job_c: JobForCreate,
+	) -> Result<i64> {
+		let now = now_utc();
+		let mut db = mm.db().await?;
+
+		// Json bind, so the query is built once per backend.
+		let rows = on_conn!(db, |c| {
+			sqlx::query_as::<_, (i64,)>(
+				r#"INSERT INTO job
+					(kind, payload, run_at, max_attempts, cid, ctime, mid, mtime)
+				VALUES ($1, $2, $3, $4, $5, $6, $5, $6)
+				RETURNING id"#,
+			)
+			.bind(job_c.kind)
+			.bind(Json(job_c.payload))
+			.bind(job_c.run_at.unwrap_or(now))
+			.bind(job_c.max_attempts.unwrap_or(Self::DEFAULT_MAX_ATTEMPTS))
+			.bind(ctx.user_id())
+			.bind(now)
+			.fetch_all(c)
+			.await?
+		});
+		let (id,) = first_row(rows)?;
+
+		Ok(id)
+	}
+
+	/// Lease up to `limit` due jobs (or jobs of an expired lease) to the
+	/// worker, for `lease`. Counts as an attempt. Oldest `run_at` first.
+	///
+	/// A job whose lease expired on its last attempt is buried instead.
+	pub async fn claim(
+		mm: &ModelManager,
+		worker_id: &str,
+		limit: i64,
+		lease: Duration,
+	) -> Result<Vec<Job>> {
+		let now = now_utc();
+		Self::bury_expired(mm, now).await?;
+
+		let mut sql = Sql::new(mm.dialect(), "UPDATE job SET status = 'running'");
+		sql.push(", locked_by = ").push_bind(worker_id);
+		sql.push(", locked_until = ").push_bind(now + lease);
+		sql.push(", attempts = attempts + 1, mid = ")
+			.push_bind(ROOT_ID);
+		sql.push(", mtime = ").push_bind(now);
+		sql.push(" WHERE id IN (SELECT id FROM job");
+		sql.push(" WHERE (status = 'pending' AND run_at <= ")
+			.push_bind(now);
+		sql.push(") OR (status = 'running' AND locked_until < ")
+			.push_bind(now);
+		sql.push(" AND attempts < max_attempts");
+		sql.push(") ORDER BY run_at, id LIMIT ").push_bind(limit);
+		// SQLite serializes the writes, so the UPDATE alone is atomic.
+		if sql.dialect() == Dialect::Postgres {
+			sql.push(" FOR UPDATE SKIP LOCKED");
+		}
+		sql.push(&format!(") RETURNING {JOB_COLUMNS}"));
+
+		let mut db = mm.db().await?;
+		let mut jobs = sql.fetch_all::<Job>(&mut db).await?;
+		jobs.sort_by_key(|job| (job.run_at, job.id));
+
+		Ok(jobs)
+	}
+
+	/// Bury the jobs whose lease expired on their last attempt (e.g., their
+	/// worker crashed), returning their number.
+	async fn bury_expired(mm: &ModelManager, now: OffsetDateTime) -> Result<u64> {
+		let mut sql = Sql::new(mm.dialect(), "UPDATE job SET status = ");
+		sql.push_bind(JobStatus::Dead.as_ref());
+		sql.push(", locked_by = NULL, locked_until = NULL");
+		sql.push(", last_error = 'lease expired on the last attempt', mid = ");
+		sql.push_bind(ROOT_ID);
+		sql.push(", mtime = ").push_bind(now);
+		sql.push(" WHERE status = 'running' AND locked_until < ")
+			.push_bind(now);
+		sql.push(" AND attempts >= max_attempts");
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		Ok(count)
+	}
+
+	/// The job succeeded, and is done.
+	pub async fn complete(
+		mm: &ModelManager,
+		id: i64,
+		worker_id: &str,
+	) -> Result<()> {
+		Self::release(mm, id, worker_id, JobStatus::Done, None, None, false).await
+	}
+
+	/// The job succeeded, and must run again at `run_at` (e.g., next poll).
+	/// Its attempts are reset.
+	pub async fn reschedule(
+		mm: &ModelManager,
+		id: i64,
+		worker_id: &str,
+		run_at: OffsetDateTime,
+	) -> Result<()> {
+		let status = JobStatus::Pending;
+		Self::release(mm, id, worker_id, status, Some(run_at), None, true).await
+	}
+
+	/// The job failed, and is retried at `run_at`.
+	pub async fn fail(
+		mm: &ModelManager,
+		id: i64,
+		worker_id: &str,
+		run_at: OffsetDateTime,
+		error: &str,
+	) -> Result<()> {
+		let status = JobStatus::Pending;
+		Self::release(mm, id, worker_id, status, Some(run_at), Some(error), false)
+			.await
+	}
+
+	/// The job failed its last attempt, and is dead-lettered.
+	pub async fn bury(
+		mm: &ModelManager,
+		id: i64,
+		worker_id: &str,
+		error: &str,
+	) -> Result<()> {
+		let status = JobStatus::Dead;
+		Self::release(mm, id, worker_id, status, None, Some(error), false).await
+	}
+
+	/// End the lease of the worker. A no-op if the lease was taken over.
+	async fn release(
+		mm: &ModelManager,
+		id: i64,
+		worker_id: &str,
+		status: JobStatus,
+		run_at: Option<OffsetDateTime>,
+		error: Option<&str>,
+		reset_attempts: bool,
+	) -> Result<()> {
+		let mut sql = Sql::new(mm.dialect(), "UPDATE job SET status = ");
+		sql.push_bind(status.as_ref());
+		sql.push(", locked_by = NULL, locked_until = NULL, mid = ");
+		sql.push_bind(ROOT_ID);
+		sql.push(", mtime = ").push_bind(now_utc());
+		if let Some(run_at) = run_at {
+			sql.push(", run_at = ").push_bind(run_at);
+		}
+		if let Some(error) = error {
+			sql.push(", last_error = ").push_bind(error);
+		}
+		if reset_attempts {
+			sql.push(", attempts = 0, last_error = NULL");
+		}
+		sql.push(" WHERE id = ").push_bind(id);
+		sql.push(" AND locked_by = ").push_bind(worker_id);
+
+		let mut db = mm.db().await?;
+		sql.execute(&mut db).await?;
+
+		Ok(())
+	}
+
+	/// Latest jobs first.
+	pub async fn list(mm: &ModelManager, filter: JobFilter) -> Result<Vec<Job>> {
+		let limit = filter
+			.limit
+			.unwrap_or(Self::DEFAULT_LIMIT)
+			.clamp(1, Self::MAX_LIMIT);
+
+		let mut sql = Sql::new(
+			mm.dialect(),
+			format!("SELECT {JOB_COLUMNS} FROM job WHERE TRUE"),
+		);
+		if let Some(status) = filter.status {
+			sql.push(" AND status = ").push_bind(status.as_ref());
+		}
+		if let Some(kind) = filter.kind {
+			sql.push(" AND kind = ").push_bind(kind);
+		}
+		sql.push(" ORDER BY id DESC LIMIT ").push_bind(limit);
+
+		let mut db = mm.db().await?;
+		let jobs = sql.fetch_all::<Job>(&mut db).await?;
+
+		Ok(jobs)
+	}
+
+	/// The jobs of `kind` not done: pending, running, or dead.
+	pub async fn list_not_done(mm: &ModelManager, kind: &str) -> Result<Vec<Job>> {
+		let mut sql = Sql::new(
+			mm.dialect(),
+			format!("SELECT {JOB_COLUMNS} FROM job WHERE status <> 'done'"),
+		);
+		sql.push(" AND kind = ").push_bind(kind);
+
+		let mut db = mm.db().await?;
+		let jobs = sql.fetch_all::<Job>(&mut db).await?;
+
+		Ok(jobs)
+	}
+
+	/// Delete the done jobs last updated before `before`, returning their
+	/// number. (Dead jobs are kept for the admins.)
+	pub async fn delete_done_before(
+		mm: &ModelManager,
+		before: OffsetDateTime,
+	) -> Result<u64> {
+		let mut sql =
+			Sql::new(mm.dialect(), "DELETE FROM job WHERE status = 'done'");
+		sql.push(" AND mtime < ").push_bind(before);
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		Ok(count)
+	}
+
+	/// Run a dead (or pending) job now, with its attempts reset.
+	pub async fn retry_now(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let mut sql = Sql::new(mm.dialect(), "UPDATE job SET status = 'pending'");
+		sql.push(", attempts = 0, run_at = ").push_bind(now_utc());
+		sql.push(", mid = ").push_bind(ctx.user_id());
+		sql.push(", mtime = ").push_bind(now_utc());
+		sql.push(" WHERE id = ").push_bind(id);
+		sql.push(" AND status IN ('dead', 'pending')");
+
+		let mut db = mm.db().await?;
+		if sql.execute(&mut db).await? > 0 {
+			return Ok(());
+		}
+
+		// -- Tell a missing job from a running or done one.
+		let mut sql = Sql::new(mm.dialect(), "SELECT status FROM job WHERE id = ");
+		sql.push_bind(id);
+
+		match sql.fetch_optional::<(String,)>(&mut db).await? {
+			Some((status,)) => Err(Error::JobNotRetryable { id, status }),
+			None => Err(Error::EntityNotFound { entity: "job", id }),
+		}
+	}
+}
+// endregion: --- JobBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use serde_json::json;
+
+	const LEASE: Duration = Duration::from_secs(60);
+
+	fn fx_job(max_attempts: i32) -> JobForCreate {
+		JobForCreate {
+			kind: "fx_kind".to_string(),
+			payload: json!({ "n": 1 }),
+			run_at: None,
+			max_attempts: Some(max_attempts),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_claim_once_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let ctx = Ctx::root_ctx();
+		let id = JobBmc::enqueue(&ctx, &mm, fx_job(3)).await?;
+
+		// -- Exec
+		let claimed = JobBmc::claim(&mm, "worker-a", 10, LEASE).await?;
+		let claimed_again = JobBmc::claim(&mm, "worker-b", 10, LEASE).await?;
+
+		// -- Check
+		assert_eq!(claimed.len(), 1);
+		assert_eq!(claimed[0].id, id);
+		assert_eq!(claimed[0].attempts, 1);
+		assert_eq!(claimed[0].locked_by.as_deref(), Some("worker-a"));
+		assert_eq!(claimed[0].payload.0, json!({ "n": 1 }));
+		assert!(claimed_again.is_empty(), "leased job claimed again");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_claim_expired_lease_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let ctx = Ctx::root_ctx();
+		let id_retried = JobBmc::enqueue(&ctx, &mm, fx_job(2)).await?;
+		let id_exhausted = JobBmc::enqueue(&ctx, &mm, fx_job(1)).await?;
+		JobBmc::claim(&mm, "worker-a", 10, Duration::ZERO).await?;
+		tokio::time::sleep(Duration::from_millis(10)).await;
+
+		// -- Exec
+		let claimed = JobBmc::claim(&mm, "worker-b", 10, LEASE).await?;
+		let dead_jobs = JobBmc::list(
+			&mm,
+			JobFilter {
+				status: Some(JobStatus::Dead),
+				..Default::default()
+			},
+		)
+		.await?;
+
+		// -- Check
+		assert_eq!(claimed.len(), 1);
+		assert_eq!(claimed[0].id, id_retried);
+		assert_eq!(claimed[0].attempts, 2);
+		assert_eq!(dead_jobs.len(), 1);
+		assert_eq!(dead_jobs[0].id, id_exhausted);
+		assert!(dead_jobs[0].locked_by.is_none());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_bury_retry_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let ctx = Ctx::root_ctx();
+		let id = JobBmc::enqueue(&ctx, &mm, fx_job(1)).await?;
+		JobBmc::claim(&mm, "worker-a", 10, LEASE).await?;
+
+		// -- Exec
+		JobBmc::bury(&mm, id, "worker-a", "fx failure").await?;
+		let dead_jobs = JobBmc::list(
+			&mm,
+			JobFilter {
+				status: Some(JobStatus::Dead),
+				..Default::default()
+			},
+		)
+		.await?;
+		JobBmc::retry_now(&ctx, &mm, id).await?;
+		let claimed = JobBmc::claim(&mm, "worker-b", 10, LEASE).await?;
+
+		// -- Check
+		assert_eq!(dead_jobs.len(), 1);
+		assert_eq!(dead_jobs[0].last_error.as_deref(), Some("fx failure"));
+		assert_eq!(claimed.len(), 1);
+		assert_eq!(claimed[0].attempts, 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_audit_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let (user_ctx, admin_ctx) = (Ctx::new(100)?, Ctx::new(101)?);
+		let id = JobBmc::enqueue(&user_ctx, &mm, fx_job(1)).await?;
+		JobBmc::claim(&mm, "worker-a", 10, LEASE).await?;
+		JobBmc::bury(&mm, id, "worker-a", "fx failure").await?;
+
+		// -- Exec
+		let buried = JobBmc::list(&mm, JobFilter::default()).await?;
+		JobBmc::retry_now(&admin_ctx, &mm, id).await?;
+		let retried = JobBmc::list(&mm, JobFilter::default()).await?;
+
+		// -- Check
+		assert_eq!((buried[0].cid, buried[0].mid), (100, 0));
+		assert_eq!((retried[0].cid, retried[0].mid), (100, 101));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_retry_running_err_not_retryable() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let ctx = Ctx::root_ctx();
+		let id = JobBmc::enqueue(&ctx, &mm, fx_job(3)).await?;
+		JobBmc::claim(&mm, "worker-a", 10, LEASE).await?;
+
+		// -- Exec
+		let res = JobBmc::retry_now(&ctx, &mm, id).await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::JobNotRetryable { .. })),
+			"should be JobNotRetryable, was {res:?}"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -1,18 +1,39 @@
 // region:    --- Modules
 
 mod base;
+mod error;
+pub mod filter;
+pub mod job;
+pub mod order;
+pub mod rate_snapshot;
+pub mod request_log;
+pub mod schedule_run;
 mod store;
-pub mod task;
+pub mod upstream_call;
 pub mod user;
 
-use crate::model::store::{new_db_pool, Db};
-use anyhow::Result;
+pub use self::error::{Error, Result};
+pub use crate::model::store::{
+	lock_key, DbLock, Dialect, Error as StoreError, MigrationStatus,
+};
+
+use crate::config;
+use crate::model::store::{new_db_pool, Db, DbConn, DbTxn, ScratchFile, Sql, Txn};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 // endregion: --- Modules
 
+/// The entry point of the model layer, handed to all the BMC functions.
+///
+/// `begin` returns a manager bound to a transaction: the BMC calls made
+/// with it (or its clones) are committed or rolled back together.
 #[derive(Clone)]
 pub struct ModelManager {
 	db: Db,
+	txn: Option<Txn>,
+	/// Set for a scratch database, removed once the last clone is dropped.
+	_scratch: Option<Arc<ScratchFile>>,
 }
 
 impl ModelManager {
@@ -20,12 +41,134 @@ impl ModelManager {
 	pub async fn new() -> Result<Self> {
 		let db = new_db_pool().await?;
 
-		Ok(ModelManager { db })
+		Ok(ModelManager {
+			db,
+			txn: None,
+			_scratch: None,
+		})
 	}
 
-	/// Returns the sqlx db pool reference.
+	/// An isolated scratch (SQLite) database, migrated and seeded, for the
+	/// tests. Each call gets its own, so tests can run in parallel.
+	///
+	/// NOTE: As with Postgres, reads see only committed data, and do not
+	///       wait for an open transaction (`begin`). Writes do wait for it
+	///       to end (SQLite has a single writer).
+	#[cfg(test)]
+	pub async fn new_scratch() -> Result<Self> {
+		let (db, file) = store::new_scratch_db().await?;
+		let mm = ModelManager {
+			db,
+			txn: None,
+			_scratch: Some(Arc::new(file)),
+		};
+		mm.migrate().await?;
+		mm.seed().await?;
+
+		Ok(mm)
+	}
+
+	/// The SQL dialect of the database, from the `database.url` scheme.
+	pub fn dialect(&self) -> Dialect {
+		self.db.dialect()
+	}
+
+	/// Returns the connection to run the queries on: the transaction one
+	/// if bound to a transaction, otherwise one from the pool.
 	/// (Only for the model layer)
-	pub(in crate::model) fn db(&self) -> &Db {
-		&self.db
+	///
+	/// NOTE: Get it after any nested model call, as the transaction
+	///       connection is locked until it is dropped.
+	pub(in crate::model) async fn db(&self) -> Result<DbConn<'_>> {
+		Ok(DbConn::acquire(&self.db, self.txn.as_ref()).await?)
+	}
+
+	// region:    --- Transactions
+	/// Start a transaction, returning a manager bound to it.
+	///
+	/// If neither `commit` nor `rollback` is called, the transaction is
+	/// rolled back when the last clone of the manager is dropped.
+	pub async fn begin(&self) -> Result<ModelManager> {
+		if self.txn.is_some() {
+			return Err(store::Error::TxnAlreadyStarted.into());
+		}
+
+		let txn = self.db.begin().await?;
+
+		Ok(ModelManager {
+			db: self.db.clone(),
+			txn: Some(Arc::new(Mutex::new(Some(txn)))),
+			_scratch: self._scratch.clone(),
+		})
+	}
+
+	pub async fn commit(self) -> Result<()> {
+		self.take_txn().await?.commit().await?;
+
+		Ok(())
+	}
+
+	/// (Only for the tests, the transaction is rolled back once dropped)
+	#[cfg(test)]
+	pub async fn rollback(self) -> Result<()> {
+		self.take_txn().await?.rollback().await?;
+
+		Ok(())
+	}
+
+	async fn take_txn(&self) -> Result<DbTxn> {
+		let txn = self.txn.as_ref().ok_or(store::Error::TxnNotStarted)?;
+
+		Ok(txn.lock().await.take().ok_or(store::Error::TxnClosed)?)
+	}
+	// endregion: --- Transactions
+
+	/// Lock `key` across the server instances, or `None` if already locked
+	/// (see `lock_key`). Released by `DbLock::release`, or when dropped.
+	pub async fn try_lock(&self, key: i64) -> Result<Option<DbLock>> {
+		Ok(store::try_lock(&self.db, key).await?)
+	}
+
+	/// Apply the pending schema migrations, returning their versions.
+	pub async fn migrate(&self) -> Result<Vec<i64>> {
+		Ok(store::migrate(&self.db).await?)
+	}
+
+	pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+		Ok(store::status(&self.db).await?)
+	}
+
+	/// Load the seed data (`sql/seed/`), apart from the schema.
+	pub async fn seed(&self) -> Result<()> {
+		Ok(store::seed(&self.db).await?)
+	}
+
+	/// DEV ONLY - Drop and recreate the database, then migrate and seed it.
+	/// Refused unless `database.dev_reset` is set (only allowed in dev).
+	pub async fn dev_reset() -> Result<Self> {
+		let database = &config().database;
+		let root_url = database
+			.dev_reset_url
+			.as_ref()
+			.filter(|_| database.dev_reset)
+			.ok_or(store::Error::DevResetNotAllowed)?;
+
+		store::dev_reset(root_url.expose()).await?;
+
+		let mm = ModelManager::new().await?;
+		mm.migrate().await?;
+		mm.seed().await?;
+
+		Ok(mm)
+	}
+
+	/// Round trip a trivial query, to check the pool can reach the database.
+	pub async fn ping(&self) -> Result<()> {
+		let mut db = self.db().await?;
+		Sql::new(self.dialect(), "SELECT 1")
+			.execute(&mut db)
+			.await?;
+
+		Ok(())
 	}
 }
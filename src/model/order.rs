@@ -0,0 +1,422 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, impl_data_fields, DbBmc};
+use crate::model::filter::{Filter, ListOptions};
+use crate::model::store::Sql;
+use crate::model::{ModelManager, Result};
+use crate::utils::Secret;
+use serde::{Deserialize, Serialize};
+use sqlb::Fields;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:    --- Order Types
+/// FixedFloat statuses after which an order does not change anymore.
+pub const FINAL_STATUSES: &[&str] = &["DONE", "EXPIRED", "EMERGENCY"];
+
+/// A FixedFloat order placed through the service, owned by the user
+/// who created it.
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Order {
+	pub id: i64,
+	pub owner_id: i64,
+
+	// -- FixedFloat order reference
+	pub ff_id: String,
+	/// Never sent back to the client (nor logged).
+	#[sqlx(try_from = "String")]
+	#[serde(skip_serializing)]
+	pub ff_token: Secret<String>,
+
+	pub order_type: String,
+	pub from_ccy: String,
+	pub to_ccy: String,
+	pub from_amount: String,
+	pub to_amount: String,
+	pub to_address: String,
+	pub status: String,
+
+	/// Optimistic concurrency version, see `OrderBmc::update`.
+	pub version: i64,
+
+	// -- Timestamps
+	//    (creator and last modifier user_id/time)
+	pub cid: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+impl Order {
+	pub fn is_final(&self) -> bool {
+		FINAL_STATUSES.contains(&self.status.as_str())
+	}
+}
+
+#[derive(Deserialize)]
+pub struct OrderForCreate {
+	pub ff_id: String,
+	pub ff_token: String,
+
+	pub order_type: String,
+	pub from_ccy: String,
+	pub to_ccy: String,
+	pub from_amount: String,
+	pub to_amount: String,
+	pub to_address: String,
+	pub status: String,
+}
+
+#[derive(Default, Deserialize)]
+pub struct OrderForUpdate {
+	pub status: Option<String>,
+}
+
+impl_data_fields!(OrderForCreate {
+	ff_id,
+	ff_token,
+	order_type,
+	from_ccy,
+	to_ccy,
+	from_amount,
+	to_amount,
+	to_address,
+	status,
+});
+impl_data_fields!(OrderForUpdate { status });
+// endregion: --- Order Types
+
+// region:    --- OrderBmc
+pub struct OrderBmc;
+
+impl DbBmc for OrderBmc {
+	const TABLE: &'static str = "order";
+	const OWNER_SCOPED: bool = true;
+	const VERSIONED: bool = true;
+}
+
+impl OrderBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		order_c: OrderForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, order_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Order> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	/// Returns the order with the given FixedFloat order id,
+	/// if visible from the `ctx`.
+	pub async fn first_by_ff_id(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		ff_id: &str,
+	) -> Result<Option<Order>> {
+		let mut sql = base::select::<Self, Order>(mm, ctx, None)?;
+		sql.push(" AND ff_id = ").push_bind(ff_id);
+
+		let mut db = mm.db().await?;
+		let order = sql.fetch_optional::<Order>(&mut db).await?;
+
+		Ok(order)
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Filter>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Order>> {
+		base::list::<Self, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn count(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Filter>,
+	) -> Result<i64> {
+		base::count::<Self, Order>(ctx, mm, filter).await
+	}
+
+	/// Update the order if still at `version` (its value when read),
+	/// returning the new version.
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		version: i64,
+		order_u: OrderForUpdate,
+	) -> Result<i64> {
+		base::update_versioned::<Self, _>(ctx, mm, id, version, order_u).await
+	}
+
+	/// Number of orders per status, across all owners.
+	pub async fn count_by_status(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+	) -> Result<Vec<(String, i64)>> {
+		let sql = Sql::new(
+			mm.dialect(),
+			r#"SELECT status, count(*) FROM "order" GROUP BY status"#,
+		);
+
+		let mut db = mm.db().await?;
+		let counts = sql.fetch_all::<(String, i64)>(&mut db).await?;
+
+		Ok(counts)
+	}
+}
+// endregion: --- OrderBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::user::{User, UserBmc, UserForCreate};
+	use crate::model::Error;
+	use anyhow::Result;
+
+	async fn fx_mm_and_ctx() -> Result<(ModelManager, Ctx)> {
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+
+		Ok((mm, Ctx::new(demo.id)?))
+	}
+
+	fn fx_order(ff_id: &str, status: &str) -> OrderForCreate {
+		OrderForCreate {
+			ff_id: ff_id.to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: status.to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_create_get_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+
+		// -- Exec
+		let id = OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		let order = OrderBmc::get(&ctx, &mm, id).await?;
+
+		// -- Check
+		assert_eq!(order.ff_id, "FX01");
+		assert_eq!(order.owner_id, ctx.user_id());
+		assert_eq!(order.cid, ctx.user_id());
+		assert_eq!(order.version, 0);
+		assert_eq!(order.ff_token.expose(), "fx-token");
+		let order_json = serde_json::to_value(&order)?;
+		assert!(order_json.get("ff_token").is_none(), "ff_token serialized");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_other_owner_err_not_found() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let id = OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+
+		// -- Exec
+		let res = OrderBmc::get(&Ctx::new(ctx.user_id() + 1)?, &mm, id).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(Error::EntityNotFound {
+					entity: "order",
+					..
+				})
+			),
+			"should be EntityNotFound, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_list_filter_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let fx_orders = [("FX01", "NEW"), ("FX02", "DONE"), ("FX03", "PENDING")];
+		for (ff_id, status) in fx_orders {
+			OrderBmc::create(&ctx, &mm, fx_order(ff_id, status)).await?;
+		}
+		let filter: Filter =
+			serde_json::from_str(r#"{"status": {"$in": ["NEW", "PENDING"]}}"#)?;
+		let list_options = ListOptions {
+			order_bys: Some(vec!["!ff_id".to_string()]),
+			..Default::default()
+		};
+
+		// -- Exec
+		let orders =
+			OrderBmc::list(&ctx, &mm, Some(filter.clone()), Some(list_options))
+				.await?;
+		let count = OrderBmc::count(&ctx, &mm, Some(filter)).await?;
+
+		// -- Check
+		let ff_ids: Vec<&str> = orders.iter().map(|o| o.ff_id.as_str()).collect();
+		assert_eq!(ff_ids, ["FX03", "FX01"]);
+		assert_eq!(count, 2);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_list_cursor_other_owner_empty() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let other_id = UserBmc::create(
+			&Ctx::root_ctx(),
+			&mm,
+			UserForCreate {
+				username: "fx_other".to_string(),
+				pwd_clear: "fx_other pwd".to_string(),
+			},
+		)
+		.await?;
+		let other_ctx = Ctx::new(other_id)?;
+		let cursor = OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		OrderBmc::create(&other_ctx, &mm, fx_order("FX02", "NEW")).await?;
+		let list_options = ListOptions {
+			cursor: Some(cursor),
+			..Default::default()
+		};
+
+		// -- Exec
+		let orders: Vec<Order> =
+			OrderBmc::list(&other_ctx, &mm, None, Some(list_options)).await?;
+
+		// -- Check
+		assert!(orders.is_empty(), "the cursor row of another owner matched");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_update_stale_version_err_conflict() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let id = OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		let fx_update = || OrderForUpdate {
+			status: Some("PENDING".to_string()),
+		};
+
+		// -- Exec
+		let version = OrderBmc::update(&ctx, &mm, id, 0, fx_update()).await?;
+		let res = OrderBmc::update(&ctx, &mm, id, 0, fx_update()).await;
+
+		// -- Check
+		assert_eq!(version, 1);
+		assert!(
+			matches!(res, Err(Error::VersionConflict { current: 1, .. })),
+			"should be VersionConflict, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_update_audit_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let id = OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		let root_ctx = Ctx::root_ctx();
+		let order_u = OrderForUpdate {
+			status: Some("PENDING".to_string()),
+		};
+
+		// -- Exec
+		OrderBmc::update(&root_ctx, &mm, id, 0, order_u).await?;
+
+		// -- Check
+		let order = OrderBmc::get(&ctx, &mm, id).await?;
+		assert_eq!(order.cid, ctx.user_id());
+		assert_eq!(order.mid, root_ctx.user_id());
+		assert!(order.mtime >= order.ctime, "mtime before ctime");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_txn_commit_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+
+		// -- Exec
+		// Sequential and concurrent calls share the transaction connection.
+		let txn_mm = mm.begin().await?;
+		let id = OrderBmc::create(&ctx, &txn_mm, fx_order("FX01", "NEW")).await?;
+		let (order, id_2) = tokio::try_join!(
+			OrderBmc::get(&ctx, &txn_mm, id),
+			OrderBmc::create(&ctx, &txn_mm, fx_order("FX02", "NEW")),
+		)?;
+		txn_mm.commit().await?;
+
+		// -- Check
+		assert_eq!(order.ff_id, "FX01");
+		assert_eq!(OrderBmc::get(&ctx, &mm, id_2).await?.ff_id, "FX02");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_txn_open_read_outside_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		OrderBmc::create(&ctx, &mm, fx_order("FX01", "NEW")).await?;
+		let txn_mm = mm.begin().await?;
+		OrderBmc::create(&ctx, &txn_mm, fx_order("FX02", "NEW")).await?;
+
+		// -- Exec
+		// Should not wait for the transaction.
+		let count = tokio::time::timeout(
+			std::time::Duration::from_secs(5),
+			OrderBmc::count(&ctx, &mm, None),
+		)
+		.await??;
+		txn_mm.rollback().await?;
+
+		// -- Check
+		// Only the committed order, the transaction one is not visible.
+		assert_eq!(count, 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_txn_rollback_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+
+		// -- Exec
+		let txn_mm = mm.begin().await?;
+		let id = OrderBmc::create(&ctx, &txn_mm, fx_order("FX01", "NEW")).await?;
+		txn_mm.rollback().await?;
+
+		// -- Check
+		let res = OrderBmc::get(&ctx, &mm, id).await;
+		assert!(
+			matches!(res, Err(Error::EntityNotFound { .. })),
+			"should be rolled back, was {res:?}"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,297 @@
+//! FixedFloat price quotes captured periodically (see the `scheduler`),
+//! aggregated into OHLC candles for the rate history.
+
+use crate::model::store::{first_row, on_conn, Sql};
+use crate::model::{ModelManager, Result};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+// region:    --- RateSnapshot Types
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RateSnapshot {
+	pub id: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub ctime: OffsetDateTime,
+
+	/// `fixed` or `float`
+	pub order_type: String,
+	pub from_ccy: String,
+	pub to_ccy: String,
+	/// Quoted amount, in `from_ccy`.
+	pub amount: String,
+
+	pub rate: f64,
+	/// Order limits, in `from_ccy`.
+	pub min_amount: Option<f64>,
+	pub max_amount: Option<f64>,
+	/// USD value of the quoted amount.
+	pub usd: Option<f64>,
+}
+
+pub struct RateSnapshotForCreate {
+	pub ctime: OffsetDateTime,
+
+	pub order_type: String,
+	pub from_ccy: String,
+	pub to_ccy: String,
+	pub amount: String,
+
+	pub rate: f64,
+	pub min_amount: Option<f64>,
+	pub max_amount: Option<f64>,
+	pub usd: Option<f64>,
+}
+
+/// The snapshots of a pair and order type, from `from` (included)
+/// to `to` (excluded).
+pub struct RateHistoryFilter {
+	pub from_ccy: String,
+	pub to_ccy: String,
+	pub order_type: String,
+	pub from: OffsetDateTime,
+	pub to: OffsetDateTime,
+}
+
+/// The rates captured over an interval (starting at `time`).
+#[derive(Debug, Serialize)]
+pub struct RateOhlc {
+	#[serde(with = "time::serde::rfc3339")]
+	pub time: OffsetDateTime,
+	pub open: f64,
+	pub high: f64,
+	pub low: f64,
+	pub close: f64,
+	/// USD value of the quoted amount, at the last snapshot having one.
+	pub usd: Option<f64>,
+	pub count: i64,
+}
+// endregion: --- RateSnapshot Types
+
+// region:    --- RateSnapshotBmc
+pub struct RateSnapshotBmc;
+
+impl RateSnapshotBmc {
+	pub async fn create(
+		mm: &ModelManager,
+		snapshot_c: RateSnapshotForCreate,
+	) -> Result<i64> {
+		let mut db = mm.db().await?;
+
+		// Typed nullable binds, so the query is built once per backend.
+		let rows = on_conn!(db, |c| {
+			sqlx::query_as::<_, (i64,)>(
+				r#"INSERT INTO rate_snapshot
+					(ctime, order_type, from_ccy, to_ccy, amount,
+					 rate, min_amount, max_amount, usd)
+				VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+				RETURNING id"#,
+			)
+			.bind(snapshot_c.ctime)
+			.bind(snapshot_c.order_type)
+			.bind(snapshot_c.from_ccy)
+			.bind(snapshot_c.to_ccy)
+			.bind(snapshot_c.amount)
+			.bind(snapshot_c.rate)
+			.bind(snapshot_c.min_amount)
+			.bind(snapshot_c.max_amount)
+			.bind(snapshot_c.usd)
+			.fetch_all(c)
+			.await?
+		});
+		let (id,) = first_row(rows)?;
+
+		Ok(id)
+	}
+
+	/// Oldest snapshots first.
+	pub async fn list(
+		mm: &ModelManager,
+		filter: &RateHistoryFilter,
+	) -> Result<Vec<RateSnapshot>> {
+		let mut sql = Sql::new(
+			mm.dialect(),
+			r#"SELECT id, ctime, order_type, from_ccy, to_ccy, amount,
+				rate, min_amount, max_amount, usd
+			FROM rate_snapshot
+			WHERE from_ccy = "#,
+		);
+		sql.push_bind(filter.from_ccy.as_str());
+		sql.push(" AND to_ccy = ").push_bind(filter.to_ccy.as_str());
+		sql.push(" AND order_type = ")
+			.push_bind(filter.order_type.as_str());
+		sql.push(" AND ctime >= ").push_bind(filter.from);
+		sql.push(" AND ctime < ").push_bind(filter.to);
+		sql.push(" ORDER BY ctime, id");
+
+		let mut db = mm.db().await?;
+		let snapshots = sql.fetch_all::<RateSnapshot>(&mut db).await?;
+
+		Ok(snapshots)
+	}
+
+	/// The snapshots aggregated per `interval`, the intervals aligned on
+	/// the unix epoch. Intervals without snapshots are left out.
+	pub async fn history(
+		mm: &ModelManager,
+		filter: &RateHistoryFilter,
+		interval: Duration,
+	) -> Result<Vec<RateOhlc>> {
+		let snapshots = Self::list(mm, filter).await?;
+
+		Ok(aggregate_ohlc(&snapshots, interval))
+	}
+
+	/// Delete the snapshots older than `before`, returning their number.
+	pub async fn delete_before(
+		mm: &ModelManager,
+		before: OffsetDateTime,
+	) -> Result<u64> {
+		let mut sql =
+			Sql::new(mm.dialect(), "DELETE FROM rate_snapshot WHERE ctime < ");
+		sql.push_bind(before);
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		Ok(count)
+	}
+}
+// endregion: --- RateSnapshotBmc
+
+// region:    --- Support
+/// OHLC candles of the snapshots, sorted by time.
+fn aggregate_ohlc(snapshots: &[RateSnapshot], interval: Duration) -> Vec<RateOhlc> {
+	let interval_sec = interval.as_secs().max(1) as i64;
+	let mut candles: Vec<RateOhlc> = Vec::new();
+
+	for snapshot in snapshots {
+		let offset_sec = snapshot.ctime.unix_timestamp().rem_euclid(interval_sec);
+		let start = snapshot.ctime
+			- time::Duration::new(offset_sec, snapshot.ctime.nanosecond() as i32);
+
+		match candles.last_mut() {
+			Some(candle) if candle.time == start => {
+				candle.high = candle.high.max(snapshot.rate);
+				candle.low = candle.low.min(snapshot.rate);
+				candle.close = snapshot.rate;
+				candle.usd = snapshot.usd.or(candle.usd);
+				candle.count += 1;
+			}
+			_ => candles.push(RateOhlc {
+				time: start,
+				open: snapshot.rate,
+				high: snapshot.rate,
+				low: snapshot.rate,
+				close: snapshot.rate,
+				usd: snapshot.usd,
+				count: 1,
+			}),
+		}
+	}
+
+	candles
+}
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use time::format_description::well_known::Rfc3339;
+
+	fn fx_time(time: &str) -> Result<OffsetDateTime> {
+		Ok(OffsetDateTime::parse(time, &Rfc3339)?)
+	}
+
+	fn fx_snapshot(ctime: &str, rate: f64) -> Result<RateSnapshotForCreate> {
+		Ok(RateSnapshotForCreate {
+			ctime: fx_time(ctime)?,
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			amount: "0.01".to_string(),
+			rate,
+			min_amount: Some(0.001),
+			max_amount: Some(1.5),
+			usd: Some(rate * 10.),
+		})
+	}
+
+	#[tokio::test]
+	async fn test_history_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let fx_snapshots = [
+			("2026-10-19T09:59:00Z", 0.5),
+			("2026-10-19T10:05:00Z", 1.0),
+			("2026-10-19T10:20:00Z", 3.0),
+			("2026-10-19T10:40:00Z", 0.5),
+			("2026-10-19T10:55:00Z", 2.0),
+			("2026-10-19T12:10:00Z", 4.0),
+			("2026-10-19T13:00:00Z", 9.0),
+		];
+		for (ctime, rate) in fx_snapshots {
+			RateSnapshotBmc::create(&mm, fx_snapshot(ctime, rate)?).await?;
+		}
+		let filter = RateHistoryFilter {
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			order_type: "fixed".to_string(),
+			from: fx_time("2026-10-19T10:00:00Z")?,
+			to: fx_time("2026-10-19T13:00:00Z")?,
+		};
+
+		// -- Exec
+		let candles =
+			RateSnapshotBmc::history(&mm, &filter, Duration::from_secs(3600))
+				.await?;
+
+		// -- Check
+		assert_eq!(candles.len(), 2, "10h and 12h candles");
+		let candle = &candles[0];
+		assert_eq!(candle.time, fx_time("2026-10-19T10:00:00Z")?);
+		assert_eq!(
+			(candle.open, candle.high, candle.low, candle.close),
+			(1.0, 3.0, 0.5, 2.0)
+		);
+		assert_eq!(candle.usd, Some(20.));
+		assert_eq!(candle.count, 4);
+		assert_eq!(candles[1].time, fx_time("2026-10-19T12:00:00Z")?);
+		assert_eq!(candles[1].count, 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_delete_before_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		for ctime in ["2026-10-18T10:00:00Z", "2026-10-19T10:00:00Z"] {
+			RateSnapshotBmc::create(&mm, fx_snapshot(ctime, 1.0)?).await?;
+		}
+		let filter = RateHistoryFilter {
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			order_type: "fixed".to_string(),
+			from: fx_time("2026-10-01T00:00:00Z")?,
+			to: fx_time("2026-10-20T00:00:00Z")?,
+		};
+
+		// -- Exec
+		let count =
+			RateSnapshotBmc::delete_before(&mm, fx_time("2026-10-19T00:00:00Z")?)
+				.await?;
+
+		// -- Check
+		assert_eq!(count, 1);
+		let snapshots = RateSnapshotBmc::list(&mm, &filter).await?;
+		assert_eq!(snapshots.len(), 1);
+		assert_eq!(snapshots[0].ctime, fx_time("2026-10-19T10:00:00Z")?);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,79 @@
+use crate::model::store::{on_conn, Sql};
+use crate::model::{ModelManager, Result};
+use serde_json::Value;
+use sqlx::types::Json;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// region:    --- RequestLogBmc
+/// Persisted request log lines (see `log::log_request`).
+pub struct RequestLogBmc;
+
+impl RequestLogBmc {
+	const TABLE: &'static str = "request_log";
+
+	pub async fn create(mm: &ModelManager, uuid: Uuid, line: &Value) -> Result<()> {
+		let mut db = mm.db().await?;
+
+		let sql =
+			format!("INSERT INTO {} (uuid, line) VALUES ($1, $2)", Self::TABLE);
+		on_conn!(db, |c| {
+			sqlx::query(&sql)
+				.bind(uuid)
+				.bind(Json(line))
+				.execute(c)
+				.await?;
+		});
+
+		Ok(())
+	}
+
+	/// Delete the lines older than `before`, returning their number.
+	pub async fn delete_before(
+		mm: &ModelManager,
+		before: OffsetDateTime,
+	) -> Result<u64> {
+		let mut sql = Sql::new(
+			mm.dialect(),
+			format!("DELETE FROM {} WHERE ctime < ", Self::TABLE),
+		);
+		sql.push_bind(before);
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		Ok(count)
+	}
+}
+// endregion: --- RequestLogBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::now_utc;
+	use anyhow::Result;
+	use serde_json::json;
+
+	#[tokio::test]
+	async fn test_create_delete_before_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let fx_line = json!({"http_path": "/api/orders", "http_method": "GET"});
+		RequestLogBmc::create(&mm, Uuid::new_v4(), &fx_line).await?;
+
+		// -- Exec
+		let before_line =
+			RequestLogBmc::delete_before(&mm, now_utc() - time::Duration::hours(1))
+				.await?;
+		let after_line =
+			RequestLogBmc::delete_before(&mm, now_utc() + time::Duration::hours(1))
+				.await?;
+
+		// -- Check
+		assert_eq!((before_line, after_line), (0, 1));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,88 @@
+//! Last run of each scheduled job (see the `scheduler`), recorded by the
+//! instance that ran it.
+
+use crate::model::store::{on_conn, Sql};
+use crate::model::{ModelManager, Result};
+use serde::Serialize;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+const SCHEDULE_RUN_COLUMNS: &str =
+	"name, started_at, duration_ms, outcome, error, run_by";
+
+// region:    --- ScheduleRun Types
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ScheduleRun {
+	pub name: String,
+	#[serde(with = "time::serde::rfc3339")]
+	pub started_at: OffsetDateTime,
+	pub duration_ms: i64,
+	/// `ok` or `error`
+	pub outcome: String,
+	pub error: Option<String>,
+	/// Worker id of the server instance.
+	pub run_by: String,
+}
+// endregion: --- ScheduleRun Types
+
+// region:    --- ScheduleRunBmc
+pub struct ScheduleRunBmc;
+
+impl ScheduleRunBmc {
+	/// Record the run, in place of the previous one.
+	pub async fn record(mm: &ModelManager, run: ScheduleRun) -> Result<()> {
+		let mut db = mm.db().await?;
+
+		// Typed nullable binds, so the query is built once per backend.
+		on_conn!(db, |c| {
+			sqlx::query(
+				r#"INSERT INTO schedule_run
+					(name, started_at, duration_ms, outcome, error, run_by)
+				VALUES ($1, $2, $3, $4, $5, $6)
+				ON CONFLICT (name) DO UPDATE SET
+					started_at = excluded.started_at,
+					duration_ms = excluded.duration_ms,
+					outcome = excluded.outcome,
+					error = excluded.error,
+					run_by = excluded.run_by"#,
+			)
+			.bind(run.name)
+			.bind(run.started_at)
+			.bind(run.duration_ms)
+			.bind(run.outcome)
+			.bind(run.error)
+			.bind(run.run_by)
+			.execute(c)
+			.await
+			.map(|_| ())?
+		});
+
+		Ok(())
+	}
+
+	pub async fn get(mm: &ModelManager, name: &str) -> Result<Option<ScheduleRun>> {
+		let mut sql = Sql::new(
+			mm.dialect(),
+			format!("SELECT {SCHEDULE_RUN_COLUMNS} FROM schedule_run WHERE name = "),
+		);
+		sql.push_bind(name);
+
+		let mut db = mm.db().await?;
+		let run = sql.fetch_optional::<ScheduleRun>(&mut db).await?;
+
+		Ok(run)
+	}
+
+	pub async fn list(mm: &ModelManager) -> Result<Vec<ScheduleRun>> {
+		let sql = Sql::new(
+			mm.dialect(),
+			format!("SELECT {SCHEDULE_RUN_COLUMNS} FROM schedule_run ORDER BY name"),
+		);
+
+		let mut db = mm.db().await?;
+		let runs = sql.fetch_all::<ScheduleRun>(&mut db).await?;
+
+		Ok(runs)
+	}
+}
+// endregion: --- ScheduleRunBmc
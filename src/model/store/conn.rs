@@ -0,0 +1,106 @@
+//! Connections and transactions over either backend, and `on_conn!` to
+//! run the same sqlx code on both.
+
+use crate::model::store::{Db, Error, Result};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgConnection, Postgres, Sqlite, SqliteConnection, Transaction};
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Run `$body` with `$c` bound to the `&mut` connection of `$db`
+/// (a `DbConn` or a `DbTxn`). The body is compiled once per backend.
+macro_rules! on_conn {
+	($db:expr, |$c:ident| $body:expr) => {
+		match $db.conn() {
+			$crate::model::store::Conn::Postgres($c) => $body,
+			$crate::model::store::Conn::Sqlite($c) => $body,
+		}
+	};
+}
+pub(crate) use on_conn;
+
+/// The live connection of either backend.
+pub enum Conn<'c> {
+	Postgres(&'c mut PgConnection),
+	Sqlite(&'c mut SqliteConnection),
+}
+
+// region:    --- DbTxn
+pub enum DbTxn {
+	// Boxed, as much larger than the SQLite one.
+	Postgres(Box<Transaction<'static, Postgres>>),
+	Sqlite(Transaction<'static, Sqlite>),
+}
+
+impl DbTxn {
+	pub fn conn(&mut self) -> Conn<'_> {
+		match self {
+			DbTxn::Postgres(txn) => Conn::Postgres(txn),
+			DbTxn::Sqlite(txn) => Conn::Sqlite(txn),
+		}
+	}
+
+	pub async fn commit(self) -> core::result::Result<(), sqlx::Error> {
+		match self {
+			DbTxn::Postgres(txn) => txn.commit().await,
+			DbTxn::Sqlite(txn) => txn.commit().await,
+		}
+	}
+
+	pub async fn rollback(self) -> core::result::Result<(), sqlx::Error> {
+		match self {
+			DbTxn::Postgres(txn) => txn.rollback().await,
+			DbTxn::Sqlite(txn) => txn.rollback().await,
+		}
+	}
+}
+
+/// The transaction shared by the clones of a transactional `ModelManager`.
+/// `None` once committed or rolled back.
+pub type Txn = Arc<Mutex<Option<DbTxn>>>;
+// endregion: --- DbTxn
+
+// region:    --- DbConn
+/// A connection from the pool, or the shared transaction of a
+/// `ModelManager` returned by `begin`.
+pub enum DbConn<'a> {
+	// Boxed, as much larger than the SQLite one.
+	Postgres(Box<PoolConnection<Postgres>>),
+	Sqlite(PoolConnection<Sqlite>),
+	Txn(MutexGuard<'a, Option<DbTxn>>),
+}
+
+impl<'a> DbConn<'a> {
+	/// The transaction connection when `txn` is set, otherwise a pool one.
+	///
+	/// NOTE: With a transaction, the connection is held until dropped, so
+	///       it must not be kept across other model calls.
+	pub async fn acquire(db: &Db, txn: Option<&'a Txn>) -> Result<DbConn<'a>> {
+		if let Some(txn) = txn {
+			let guard = txn.lock().await;
+			if guard.is_none() {
+				return Err(Error::TxnClosed);
+			}
+			return Ok(DbConn::Txn(guard));
+		}
+
+		let conn = match db {
+			Db::Postgres(pool) => {
+				pool.acquire().await.map(|c| DbConn::Postgres(Box::new(c)))
+			}
+			Db::Sqlite(pool) => pool.acquire().await.map(DbConn::Sqlite),
+		};
+
+		conn.map_err(|ex| Error::FailToAcquireConn(ex.to_string()))
+	}
+
+	pub fn conn(&mut self) -> Conn<'_> {
+		match self {
+			DbConn::Postgres(conn) => Conn::Postgres(conn),
+			DbConn::Sqlite(conn) => Conn::Sqlite(conn),
+			// `acquire` checked the transaction is still open.
+			DbConn::Txn(guard) => guard.as_mut().expect("open transaction").conn(),
+		}
+	}
+}
+// endregion: --- DbConn
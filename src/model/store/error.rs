@@ -4,7 +4,35 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Serialize)]
 pub enum Error {
+	DbUrlNotSupported,
 	FailToCreatePool(String),
+	FailToAcquireConn(String),
+
+	// -- Transactions
+	TxnAlreadyStarted,
+	TxnNotStarted,
+	/// The transaction was already committed or rolled back.
+	TxnClosed,
+
+	// -- Locks
+	LockFail(String),
+
+	// -- Migrations
+	MigrationTableFail(String),
+	MigrationChecksumMismatch {
+		version: i64,
+		name: String,
+	},
+	MigrationFail {
+		version: i64,
+		cause: String,
+	},
+	SeedFail {
+		name: String,
+		cause: String,
+	},
+	DevResetNotAllowed,
+	DevResetFail(String),
 }
 
 // region:    --- Error Boilerplate
@@ -0,0 +1,74 @@
+//! Locks shared by the server instances, e.g., so a scheduled job runs
+//! on one instance at a time.
+
+use crate::model::store::{Db, DbTxn, Error, Result};
+
+/// Held until `release` (or dropped).
+///
+/// On Postgres, a transaction advisory lock, on a transaction of its own:
+/// it is released with the transaction, even if its holder crashes.
+/// A SQLite database is local to one server instance, so it is a no-op.
+pub struct DbLock(Option<DbTxn>);
+
+impl DbLock {
+	pub async fn release(self) -> Result<()> {
+		if let Some(txn) = self.0 {
+			txn.rollback()
+				.await
+				.map_err(|ex| Error::LockFail(ex.to_string()))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// The lock on `key`, or `None` if another holder has it.
+pub async fn try_lock(db: &Db, key: i64) -> Result<Option<DbLock>> {
+	let mut pg_txn = match db {
+		Db::Postgres(pool) => pool
+			.begin()
+			.await
+			.map_err(|ex| Error::FailToAcquireConn(ex.to_string()))?,
+		Db::Sqlite(_) => return Ok(Some(DbLock(None))),
+	};
+
+	let sql = "SELECT pg_try_advisory_xact_lock($1)";
+	let (locked,) = sqlx::query_as::<_, (bool,)>(sql)
+		.bind(key)
+		.fetch_one(&mut *pg_txn)
+		.await
+		.map_err(|ex| Error::LockFail(ex.to_string()))?;
+
+	// Not locked: the transaction is rolled back on drop.
+	Ok(locked.then(|| DbLock(Some(DbTxn::Postgres(Box::new(pg_txn))))))
+}
+
+/// The lock on `key`, waiting for its holder to release it.
+///
+/// Takes a pool connection of its own, for as long as it is held.
+pub async fn lock(db: &Db, key: i64) -> Result<DbLock> {
+	let mut pg_txn = match db {
+		Db::Postgres(pool) => pool
+			.begin()
+			.await
+			.map_err(|ex| Error::FailToAcquireConn(ex.to_string()))?,
+		Db::Sqlite(_) => return Ok(DbLock(None)),
+	};
+
+	sqlx::query("SELECT pg_advisory_xact_lock($1)")
+		.bind(key)
+		.execute(&mut *pg_txn)
+		.await
+		.map_err(|ex| Error::LockFail(ex.to_string()))?;
+
+	Ok(DbLock(Some(DbTxn::Postgres(Box::new(pg_txn)))))
+}
+
+/// A stable lock key for a name (64-bit FNV-1a).
+pub fn lock_key(name: &str) -> i64 {
+	let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+	});
+
+	hash as i64
+}
@@ -0,0 +1,313 @@
+//! Forward-only schema migrations, tracked with their checksum in the
+//! `schema_migration` table. Seed data is kept apart, in `sql/seed/`.
+//!
+//! Each migration and seed has a Postgres and a SQLite version, in the
+//! `postgres/` and `sqlite/` folders, with the same file name.
+
+use super::lock::{lock, lock_key};
+use super::{on_conn, Db, DbConn, Dialect, Error, Result};
+use crate::utils::format_time;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, FromRow};
+use tracing::info;
+
+/// Both dialect versions of a sql file.
+macro_rules! dialect_sql {
+	($dir:literal, $file:literal) => {
+		DialectSql {
+			postgres: include_str!(concat!(
+				"../../../sql/",
+				$dir,
+				"/postgres/",
+				$file
+			)),
+			sqlite: include_str!(concat!("../../../sql/", $dir, "/sqlite/", $file)),
+		}
+	};
+}
+
+/// Name of the advisory lock held while migrating.
+const MIGRATION_LOCK: &str = "schema_migration";
+
+/// Ordered by version. Never edit an applied migration, add a new one.
+const MIGRATIONS: &[Migration] = &[
+	Migration {
+		version: 1,
+		name: "initial_schema",
+		sql: dialect_sql!("migrations", "0001_initial_schema.sql"),
+	},
+	Migration {
+		version: 2,
+		name: "audit_columns",
+		sql: dialect_sql!("migrations", "0002_audit_columns.sql"),
+	},
+	Migration {
+		version: 3,
+		name: "order_version",
+		sql: dialect_sql!("migrations", "0003_order_version.sql"),
+	},
+	Migration {
+		version: 4,
+		name: "job_queue",
+		sql: dialect_sql!("migrations", "0004_job_queue.sql"),
+	},
+	Migration {
+		version: 5,
+		name: "schedule_run",
+		sql: dialect_sql!("migrations", "0005_schedule_run.sql"),
+	},
+	Migration {
+		version: 6,
+		name: "rate_snapshot",
+		sql: dialect_sql!("migrations", "0006_rate_snapshot.sql"),
+	},
+	Migration {
+		version: 7,
+		name: "job_audit",
+		sql: dialect_sql!("migrations", "0007_job_audit.sql"),
+	},
+];
+
+const SEEDS: &[(&str, DialectSql)] =
+	&[("01-dev-users", dialect_sql!("seed", "01-dev-users.sql"))];
+
+/// Drop and recreate the app database and user (connected as a superuser).
+const DEV_RESET_SQL: &str = include_str!("../../../sql/dev_reset.sql");
+
+struct DialectSql {
+	postgres: &'static str,
+	sqlite: &'static str,
+}
+
+impl DialectSql {
+	fn get(&self, dialect: Dialect) -> &'static str {
+		match dialect {
+			Dialect::Postgres => self.postgres,
+			Dialect::Sqlite => self.sqlite,
+		}
+	}
+}
+
+struct Migration {
+	version: i64,
+	name: &'static str,
+	sql: DialectSql,
+}
+
+impl Migration {
+	/// Of the dialect version, as that is the one applied.
+	fn checksum(&self, dialect: Dialect) -> String {
+		hex::encode(Sha256::digest(self.sql.get(dialect).as_bytes()))
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+	pub version: i64,
+	pub name: String,
+	/// Rfc3339, `None` when pending.
+	pub applied_at: Option<String>,
+}
+
+#[derive(FromRow)]
+struct AppliedMigration {
+	version: i64,
+	checksum: String,
+	applied_at: time::OffsetDateTime,
+}
+
+const MIGRATION_TABLE_SQL: DialectSql = DialectSql {
+	postgres: r#"CREATE TABLE IF NOT EXISTS schema_migration (
+		version BIGINT PRIMARY KEY,
+		name VARCHAR(128) NOT NULL,
+		checksum VARCHAR(64) NOT NULL,
+		applied_at timestamp with time zone NOT NULL DEFAULT now()
+	)"#,
+	sqlite: r#"CREATE TABLE IF NOT EXISTS schema_migration (
+		version INTEGER PRIMARY KEY,
+		name TEXT NOT NULL,
+		checksum TEXT NOT NULL,
+		applied_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+	)"#,
+};
+
+async fn applied(db: &Db) -> Result<Vec<AppliedMigration>> {
+	let mut conn = DbConn::acquire(db, None).await?;
+	let table_sql = MIGRATION_TABLE_SQL.get(db.dialect());
+
+	on_conn!(conn, |c| c.execute(table_sql).await.map(|_| ()))
+		.map_err(|ex| Error::MigrationTableFail(ex.to_string()))?;
+
+	let applied = on_conn!(conn, |c| {
+		sqlx::query_as::<_, AppliedMigration>(
+			"SELECT version, checksum, applied_at FROM schema_migration",
+		)
+		.fetch_all(c)
+		.await
+	});
+
+	applied.map_err(|ex| Error::MigrationTableFail(ex.to_string()))
+}
+
+/// Apply the pending migrations, each in its own transaction.
+/// Fails if an applied migration was changed since.
+///
+/// Holds the migration lock meanwhile, so instances starting together
+/// apply each migration once.
+pub async fn migrate(db: &Db) -> Result<Vec<i64>> {
+	let lock = lock(db, lock_key(MIGRATION_LOCK)).await?;
+	let res = migrate_locked(db).await;
+	lock.release().await?;
+
+	res
+}
+
+async fn migrate_locked(db: &Db) -> Result<Vec<i64>> {
+	let applied = applied(db).await?;
+
+	let mut versions = Vec::new();
+	for migration in MIGRATIONS {
+		let checksum = migration.checksum(db.dialect());
+
+		if let Some(done) = applied.iter().find(|a| a.version == migration.version) {
+			if done.checksum != checksum {
+				return Err(Error::MigrationChecksumMismatch {
+					version: migration.version,
+					name: migration.name.to_string(),
+				});
+			}
+			continue;
+		}
+
+		info!(
+			"{:<12} - applying {:04}_{}",
+			"MIGRATE", migration.version, migration.name
+		);
+		apply(db, migration, &checksum).await.map_err(|ex| {
+			Error::MigrationFail {
+				version: migration.version,
+				cause: ex.to_string(),
+			}
+		})?;
+		versions.push(migration.version);
+	}
+
+	Ok(versions)
+}
+
+async fn apply(
+	db: &Db,
+	migration: &Migration,
+	checksum: &str,
+) -> core::result::Result<(), sqlx::Error> {
+	let mut txn = db.begin().await?;
+	let sql = migration.sql.get(db.dialect());
+
+	on_conn!(txn, |c| {
+		(&mut *c).execute(sql).await?;
+		sqlx::query(
+			"INSERT INTO schema_migration (version, name, checksum) \
+			 VALUES ($1, $2, $3)",
+		)
+		.bind(migration.version)
+		.bind(migration.name)
+		.bind(checksum)
+		.execute(c)
+		.await?;
+	});
+
+	txn.commit().await
+}
+
+pub async fn status(db: &Db) -> Result<Vec<MigrationStatus>> {
+	let applied = applied(db).await?;
+
+	let status = MIGRATIONS
+		.iter()
+		.map(|migration| MigrationStatus {
+			version: migration.version,
+			name: migration.name.to_string(),
+			applied_at: applied
+				.iter()
+				.find(|a| a.version == migration.version)
+				.map(|a| format_time(a.applied_at)),
+		})
+		.collect();
+
+	Ok(status)
+}
+
+pub async fn seed(db: &Db) -> Result<()> {
+	let mut conn = DbConn::acquire(db, None).await?;
+
+	for (name, sql) in SEEDS {
+		info!("{:<12} - seeding {name}", "MIGRATE");
+		let sql = sql.get(db.dialect());
+		let res = on_conn!(conn, |c| c.execute(sql).await.map(|_| ()));
+		res.map_err(|ex| Error::SeedFail {
+			name: name.to_string(),
+			cause: ex.to_string(),
+		})?;
+	}
+
+	Ok(())
+}
+
+/// Drop and recreate the app database, through the superuser `root_url`.
+/// Postgres only. (A SQLite dev database is a file to delete.)
+///
+/// DEV ONLY - the caller checks the explicit dev flag.
+pub async fn dev_reset(root_url: &str) -> Result<()> {
+	let root_db = PgPoolOptions::new()
+		.max_connections(1)
+		.connect(root_url)
+		.await
+		.map_err(|ex| Error::FailToCreatePool(ex.to_string()))?;
+
+	// One statement at a time, as DROP DATABASE cannot run in a transaction.
+	let statements = DEV_RESET_SQL
+		.split(';')
+		.map(str::trim)
+		.filter(|s| !s.is_empty());
+	for statement in statements {
+		root_db
+			.execute(statement)
+			.await
+			.map_err(|ex| Error::DevResetFail(ex.to_string()))?;
+	}
+
+	Ok(())
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::store::new_scratch_db;
+	use anyhow::Result;
+
+	#[tokio::test]
+	async fn test_migrate_again_noop() -> Result<()> {
+		// -- Setup & Fixtures
+		let (db, _file) = new_scratch_db().await?;
+
+		// -- Exec
+		let applied = migrate(&db).await?;
+		let applied_again = migrate(&db).await?;
+
+		// -- Check
+		assert!(!applied.is_empty(), "no migration applied");
+		assert!(applied_again.is_empty(), "migrations applied twice");
+		let statuses = status(&db).await?;
+		assert_eq!(statuses.len(), applied.len());
+		assert!(
+			statuses.iter().all(|s| s.applied_at.is_some()),
+			"pending migration left"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
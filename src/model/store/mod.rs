@@ -1,21 +1,152 @@
 // region:    --- Modules
 
+mod conn;
 mod error;
+mod lock;
+mod migrations;
+mod sql;
 
+pub(crate) use self::conn::on_conn;
+pub use self::conn::{Conn, DbConn, DbTxn, Txn};
 pub use self::error::{Error, Result};
+pub use self::lock::{lock_key, try_lock, DbLock};
+pub use self::migrations::{dev_reset, migrate, seed, status, MigrationStatus};
+pub use self::sql::{first_row, Bind, FromDbRow, IntoBind, Sql};
 
 use crate::config::config;
-use sqlx::postgres::PgPoolOptions;
-use sqlx::{Pool, Postgres};
+use serde::Serialize;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 // endregion: --- Modules
 
-pub type Db = Pool<Postgres>;
+/// The SQL dialect of the database, selected by the `database.url` scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Dialect {
+	/// `postgres://` or `postgresql://`
+	Postgres,
+	/// `sqlite:` e.g., `sqlite:dev.db` or `sqlite::memory:`
+	Sqlite,
+}
+
+impl Dialect {
+	pub fn from_url(url: &str) -> Option<Dialect> {
+		if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+			Some(Dialect::Postgres)
+		} else if url.starts_with("sqlite:") {
+			Some(Dialect::Sqlite)
+		} else {
+			None
+		}
+	}
+}
+
+#[derive(Clone)]
+pub enum Db {
+	Postgres(PgPool),
+	Sqlite(SqlitePool),
+}
+
+impl Db {
+	pub fn dialect(&self) -> Dialect {
+		match self {
+			Db::Postgres(_) => Dialect::Postgres,
+			Db::Sqlite(_) => Dialect::Sqlite,
+		}
+	}
+
+	pub async fn begin(&self) -> core::result::Result<DbTxn, sqlx::Error> {
+		match self {
+			Db::Postgres(pool) => {
+				pool.begin().await.map(|t| DbTxn::Postgres(Box::new(t)))
+			}
+			Db::Sqlite(pool) => pool.begin().await.map(DbTxn::Sqlite),
+		}
+	}
+}
+
+/// Enough for a transaction and the calls made meanwhile.
+#[cfg(test)]
+const SCRATCH_MAX_CONNECTIONS: u32 = 4;
 
 pub async fn new_db_pool() -> Result<Db> {
-	PgPoolOptions::new()
-		.max_connections(5)
-		.connect(&config().DB_URL)
+	let database = &config().database;
+
+	connect(database.url.expose(), database.max_connections).await
+}
+
+/// The file of a scratch database, removed (with its WAL files) on drop.
+pub struct ScratchFile(PathBuf);
+
+impl Drop for ScratchFile {
+	fn drop(&mut self) {
+		for suffix in ["", "-wal", "-shm"] {
+			let mut path = self.0.clone().into_os_string();
+			path.push(suffix);
+			let _ = std::fs::remove_file(path);
+		}
+	}
+}
+
+/// A new, empty, SQLite database in a temporary file, in WAL mode: as with
+/// Postgres, reads see only committed data and do not wait for an open
+/// transaction. The file is removed when the `ScratchFile` is dropped.
+#[cfg(test)]
+pub async fn new_scratch_db() -> Result<(Db, ScratchFile)> {
+	use sqlx::sqlite::SqliteJournalMode;
+	use uuid::Uuid;
+
+	let file = ScratchFile(
+		std::env::temp_dir().join(format!("fixedfloat-{}.db", Uuid::new_v4())),
+	);
+	let options = SqliteConnectOptions::new()
+		.filename(&file.0)
+		.create_if_missing(true)
+		.foreign_keys(true)
+		.journal_mode(SqliteJournalMode::Wal);
+
+	let pool = SqlitePoolOptions::new()
+		.max_connections(SCRATCH_MAX_CONNECTIONS)
+		.connect_with(options)
 		.await
-		.map_err(|ex| Error::FailToCreatePool(ex.to_string()))
+		.map_err(|ex| Error::FailToCreatePool(ex.to_string()))?;
+
+	Ok((Db::Sqlite(pool), file))
+}
+
+async fn connect(url: &str, max_connections: u32) -> Result<Db> {
+	let db = match Dialect::from_url(url) {
+		Some(Dialect::Postgres) => PgPoolOptions::new()
+			.max_connections(max_connections)
+			.connect(url)
+			.await
+			.map(Db::Postgres),
+
+		Some(Dialect::Sqlite) => {
+			let options = SqliteConnectOptions::from_str(url)
+				.map_err(|ex| Error::FailToCreatePool(ex.to_string()))?
+				.create_if_missing(true)
+				.foreign_keys(true);
+
+			// An in-memory database lives as long as its connection,
+			// so keep a single one, never recycled.
+			let pool_options = if url.contains(":memory:") {
+				SqlitePoolOptions::new()
+					.max_connections(1)
+					.min_connections(1)
+					.idle_timeout(None)
+					.max_lifetime(None)
+			} else {
+				SqlitePoolOptions::new().max_connections(max_connections)
+			};
+
+			pool_options.connect_with(options).await.map(Db::Sqlite)
+		}
+
+		None => return Err(Error::DbUrlNotSupported),
+	};
+
+	db.map_err(|ex| Error::FailToCreatePool(ex.to_string()))
 }
@@ -0,0 +1,190 @@
+//! A SQL text and its bind values, run as is on either backend
+//! (both accept the `$N` placeholders).
+
+use crate::model::store::{on_conn, DbConn, Dialect};
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+type SqlxResult<T> = core::result::Result<T, sqlx::Error>;
+
+/// Rows decodable from both backends.
+pub trait FromDbRow:
+	for<'r> FromRow<'r, PgRow> + for<'r> FromRow<'r, SqliteRow> + Send + Unpin
+{
+}
+
+impl<T> FromDbRow for T where
+	T: for<'r> FromRow<'r, PgRow> + for<'r> FromRow<'r, SqliteRow> + Send + Unpin
+{
+}
+
+// region:    --- Bind
+#[derive(Debug, Clone)]
+pub enum Bind {
+	Bool(bool),
+	I64(i64),
+	F64(f64),
+	Text(String),
+	Time(OffsetDateTime),
+	Uuid(Uuid),
+}
+
+/// A value to bind, `None` for a null (skipped) one.
+pub trait IntoBind {
+	fn into_bind(self) -> Option<Bind>;
+}
+
+macro_rules! impl_bind {
+	($($ty:ty => $variant:ident),*) => {
+		$(
+			impl From<$ty> for Bind {
+				fn from(val: $ty) -> Self {
+					Bind::$variant(val)
+				}
+			}
+
+			impl IntoBind for $ty {
+				fn into_bind(self) -> Option<Bind> {
+					Some(Bind::$variant(self))
+				}
+			}
+
+			impl IntoBind for Option<$ty> {
+				fn into_bind(self) -> Option<Bind> {
+					self.map(Bind::$variant)
+				}
+			}
+		)*
+	};
+}
+
+impl_bind!(
+	bool => Bool,
+	i64 => I64,
+	f64 => F64,
+	String => Text,
+	OffsetDateTime => Time,
+	Uuid => Uuid
+);
+
+impl From<&str> for Bind {
+	fn from(val: &str) -> Self {
+		Bind::Text(val.to_string())
+	}
+}
+
+macro_rules! bind_all {
+	($query:expr, $binds:expr) => {{
+		let mut query = $query;
+		for bind in $binds.iter().cloned() {
+			query = match bind {
+				Bind::Bool(val) => query.bind(val),
+				Bind::I64(val) => query.bind(val),
+				Bind::F64(val) => query.bind(val),
+				Bind::Text(val) => query.bind(val),
+				Bind::Time(val) => query.bind(val),
+				Bind::Uuid(val) => query.bind(val),
+			};
+		}
+		query
+	}};
+}
+// endregion: --- Bind
+
+// region:    --- Sql
+#[derive(Debug)]
+pub struct Sql {
+	dialect: Dialect,
+	sql: String,
+	binds: Vec<Bind>,
+}
+
+impl Sql {
+	pub fn new(dialect: Dialect, sql: impl Into<String>) -> Self {
+		Sql {
+			dialect,
+			sql: sql.into(),
+			binds: Vec::new(),
+		}
+	}
+
+	pub fn dialect(&self) -> Dialect {
+		self.dialect
+	}
+
+	pub fn push(&mut self, sql: &str) -> &mut Self {
+		self.sql.push_str(sql);
+		self
+	}
+
+	#[cfg(test)]
+	pub fn sql(&self) -> &str {
+		&self.sql
+	}
+
+	#[cfg(test)]
+	pub fn binds(&self) -> &[Bind] {
+		&self.binds
+	}
+
+	/// Push the `$N` placeholder of the value.
+	pub fn push_bind(&mut self, val: impl Into<Bind>) -> &mut Self {
+		self.binds.push(val.into());
+		self.sql.push_str(&format!("${}", self.binds.len()));
+		self
+	}
+
+	/// Returns the number of rows affected.
+	pub async fn execute(&self, db: &mut DbConn<'_>) -> SqlxResult<u64> {
+		on_conn!(db, |c| {
+			let res = bind_all!(sqlx::query(&self.sql), self.binds)
+				.execute(c)
+				.await?;
+			Ok(res.rows_affected())
+		})
+	}
+
+	/// NOTE: Reads the rows to the end, see `first_row`.
+	pub async fn fetch_one<E>(&self, db: &mut DbConn<'_>) -> SqlxResult<E>
+	where
+		E: FromDbRow,
+	{
+		first_row(self.fetch_all(db).await?)
+	}
+
+	/// NOTE: Reads the rows to the end, see `first_row`.
+	pub async fn fetch_optional<E>(
+		&self,
+		db: &mut DbConn<'_>,
+	) -> SqlxResult<Option<E>>
+	where
+		E: FromDbRow,
+	{
+		Ok(self.fetch_all(db).await?.into_iter().next())
+	}
+
+	pub async fn fetch_all<E>(&self, db: &mut DbConn<'_>) -> SqlxResult<Vec<E>>
+	where
+		E: FromDbRow,
+	{
+		on_conn!(db, |c| {
+			bind_all!(sqlx::query_as::<_, E>(&self.sql), self.binds)
+				.fetch_all(c)
+				.await
+		})
+	}
+}
+
+/// The first of the `rows` fetched by a query returning a single one.
+///
+/// NOTE: Fetch them all rather than one: with SQLite, sqlx returns the first
+///       row before the statement is done, and the end of an autocommit
+///       write (e.g., `INSERT .. RETURNING`) is its commit. Once all the
+///       rows are read, the next query on another connection sees it.
+pub fn first_row<T>(rows: Vec<T>) -> SqlxResult<T> {
+	rows.into_iter().next().ok_or(sqlx::Error::RowNotFound)
+}
+// endregion: --- Sql
@@ -1,64 +0,0 @@
-use crate::ctx::Ctx;
-use crate::model::base::{self, DbBmc};
-use crate::model::ModelManager;
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use sqlb::Fields;
-use sqlx::FromRow;
-
-// region:    --- Task Types
-#[derive(Debug, Clone, Fields, FromRow, Serialize)]
-pub struct Task {
-	pub id: i64,
-	pub title: String,
-}
-
-#[derive(Fields, Deserialize)]
-pub struct TaskForCreate {
-	pub title: String,
-}
-
-#[derive(Fields, Deserialize)]
-pub struct TaskForUpdate {
-	pub title: Option<String>,
-}
-// endregion: --- Task Types
-
-// region:    --- TaskBmc
-pub struct TaskBmc;
-
-impl DbBmc for TaskBmc {
-	const TABLE: &'static str = "task";
-}
-
-impl TaskBmc {
-	pub async fn create(
-		ctx: &Ctx,
-		mm: &ModelManager,
-		task_c: TaskForCreate,
-	) -> Result<i64> {
-		base::create::<Self, _>(ctx, mm, task_c).await
-	}
-
-	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Task> {
-		base::get::<Self, _>(ctx, mm, id).await
-	}
-
-	pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<Task>> {
-		base::list::<Self, _>(ctx, mm).await
-	}
-
-	pub async fn update(
-		ctx: &Ctx,
-		mm: &ModelManager,
-		id: i64,
-		task_u: TaskForUpdate,
-	) -> Result<()> {
-		base::update::<Self, _>(ctx, mm, id, task_u).await
-	}
-
-	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
-		base::delete::<Self>(ctx, mm, id).await
-	}
-}
-// endregion: --- TaskBmc
@@ -0,0 +1,273 @@
+use crate::model::store::{first_row, on_conn, Sql};
+use crate::model::{ModelManager, Result};
+use crate::utils::now_utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// region:    --- UpstreamCall Types
+/// One call to the FixedFloat API (see `FixedFloat::post`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct UpstreamCall {
+	pub id: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub ctime: OffsetDateTime,
+
+	pub method: String,
+	/// Request body, with the secret fields redacted.
+	pub request_json: Json<Value>,
+
+	pub http_status: Option<i32>,
+	pub code: Option<i32>,
+	pub msg: Option<String>,
+
+	pub latency_ms: i64,
+	pub attempt: i32,
+
+	// -- Correlation
+	pub req_uuid: Option<Uuid>,
+	pub order_id: Option<String>,
+}
+
+pub struct UpstreamCallForCreate {
+	pub method: String,
+	pub request_json: Value,
+
+	pub http_status: Option<i32>,
+	pub code: Option<i32>,
+	pub msg: Option<String>,
+
+	pub latency_ms: i64,
+	pub attempt: i32,
+
+	pub req_uuid: Option<Uuid>,
+	pub order_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpstreamCallFilter {
+	pub method: Option<String>,
+	pub order_id: Option<String>,
+	pub req_uuid: Option<Uuid>,
+	pub limit: Option<i64>,
+}
+// endregion: --- UpstreamCall Types
+
+// region:    --- UpstreamCallBmc
+pub struct UpstreamCallBmc;
+
+impl UpstreamCallBmc {
+	const DEFAULT_LIMIT: i64 = 100;
+	const MAX_LIMIT: i64 = 1000;
+
+	pub async fn create(
+		mm: &ModelManager,
+		call_c: UpstreamCallForCreate,
+	) -> Result<i64> {
+		let mut db = mm.db().await?;
+
+		// Typed nullable binds, so the query is built once per backend.
+		let rows = on_conn!(db, |c| {
+			sqlx::query_as::<_, (i64,)>(
+				r#"INSERT INTO upstream_call
+					(method, request_json, http_status, code, msg,
+					 latency_ms, attempt, req_uuid, order_id)
+				VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+				RETURNING id"#,
+			)
+			.bind(call_c.method)
+			.bind(Json(call_c.request_json))
+			.bind(call_c.http_status)
+			.bind(call_c.code)
+			.bind(call_c.msg)
+			.bind(call_c.latency_ms)
+			.bind(call_c.attempt)
+			.bind(call_c.req_uuid)
+			.bind(call_c.order_id)
+			.fetch_all(c)
+			.await?
+		});
+		let (id,) = first_row(rows)?;
+
+		Ok(id)
+	}
+
+	/// Latest calls first.
+	pub async fn list(
+		mm: &ModelManager,
+		filter: UpstreamCallFilter,
+	) -> Result<Vec<UpstreamCall>> {
+		let limit = filter
+			.limit
+			.unwrap_or(Self::DEFAULT_LIMIT)
+			.clamp(1, Self::MAX_LIMIT);
+
+		let mut sql = Sql::new(
+			mm.dialect(),
+			r#"SELECT id, ctime, method, request_json, http_status, code, msg,
+				latency_ms, attempt, req_uuid, order_id
+			FROM upstream_call
+			WHERE TRUE"#,
+		);
+		if let Some(method) = filter.method {
+			sql.push(" AND method = ").push_bind(method);
+		}
+		if let Some(order_id) = filter.order_id {
+			sql.push(" AND order_id = ").push_bind(order_id);
+		}
+		if let Some(req_uuid) = filter.req_uuid {
+			sql.push(" AND req_uuid = ").push_bind(req_uuid);
+		}
+		sql.push(" ORDER BY id DESC LIMIT ").push_bind(limit);
+
+		let mut db = mm.db().await?;
+		let calls = sql.fetch_all::<UpstreamCall>(&mut db).await?;
+
+		Ok(calls)
+	}
+
+	/// Number of calls and of calls FixedFloat answered (any non 5xx/429
+	/// http status), over the last `window_sec` seconds.
+	pub async fn answered_since(
+		mm: &ModelManager,
+		window_sec: i64,
+	) -> Result<(i64, i64)> {
+		let since = now_utc() - time::Duration::seconds(window_sec);
+		let mut sql = Sql::new(
+			mm.dialect(),
+			r#"SELECT count(*),
+				count(*) FILTER (WHERE http_status < 500 AND http_status <> 429)
+			FROM upstream_call
+			WHERE ctime > "#,
+		);
+		sql.push_bind(since);
+
+		let mut db = mm.db().await?;
+		let counts = sql.fetch_one::<(i64, i64)>(&mut db).await?;
+
+		Ok(counts)
+	}
+
+	/// Delete the calls older than `before`, returning their number.
+	pub async fn delete_before(
+		mm: &ModelManager,
+		before: OffsetDateTime,
+	) -> Result<u64> {
+		let mut sql =
+			Sql::new(mm.dialect(), "DELETE FROM upstream_call WHERE ctime < ");
+		sql.push_bind(before);
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		Ok(count)
+	}
+}
+// endregion: --- UpstreamCallBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use serde_json::json;
+
+	fn fx_call(
+		method: &str,
+		http_status: Option<i32>,
+		order_id: Option<&str>,
+	) -> UpstreamCallForCreate {
+		UpstreamCallForCreate {
+			method: method.to_string(),
+			request_json: json!({"id": order_id, "token": "***"}),
+			http_status,
+			code: http_status.map(|_| 0),
+			msg: http_status.map(|_| "OK".to_string()),
+			latency_ms: 42,
+			attempt: 1,
+			req_uuid: None,
+			order_id: order_id.map(|id| id.to_string()),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_create_list_filter_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let fx_calls = [
+			fx_call("price", Some(200), None),
+			fx_call("order", Some(200), Some("FX01")),
+			fx_call("order", Some(503), Some("FX02")),
+		];
+		for call_c in fx_calls {
+			UpstreamCallBmc::create(&mm, call_c).await?;
+		}
+
+		// -- Exec
+		let calls = UpstreamCallBmc::list(
+			&mm,
+			UpstreamCallFilter {
+				method: Some("order".to_string()),
+				..Default::default()
+			},
+		)
+		.await?;
+
+		// -- Check
+		let order_ids: Vec<_> =
+			calls.iter().map(|c| c.order_id.as_deref()).collect();
+		assert_eq!(order_ids, [Some("FX02"), Some("FX01")], "latest first");
+		assert_eq!(calls[1].request_json.0["token"], "***");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_answered_since_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let fx_calls = [
+			fx_call("price", Some(200), None),
+			fx_call("price", Some(400), None),
+			fx_call("price", Some(429), None),
+			fx_call("price", Some(502), None),
+			fx_call("price", None, None),
+		];
+		for call_c in fx_calls {
+			UpstreamCallBmc::create(&mm, call_c).await?;
+		}
+
+		// -- Exec
+		let counts = UpstreamCallBmc::answered_since(&mm, 60).await?;
+
+		// -- Check
+		assert_eq!(counts, (5, 2));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_delete_before_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		UpstreamCallBmc::create(&mm, fx_call("price", Some(200), None)).await?;
+
+		// -- Exec
+		let count = UpstreamCallBmc::delete_before(
+			&mm,
+			now_utc() + time::Duration::hours(1),
+		)
+		.await?;
+
+		// -- Check
+		assert_eq!(count, 1);
+		let calls = UpstreamCallBmc::list(&mm, Default::default()).await?;
+		assert!(calls.is_empty(), "calls left after delete");
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
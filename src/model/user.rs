@@ -1,11 +1,12 @@
 use crate::ctx::Ctx;
-use crate::model::base::{self, DbBmc};
-use crate::model::ModelManager;
-use anyhow::{Error, Result};
+use crate::model::base::{self, impl_data_fields, DbBmc};
+use crate::model::store::{Bind, FromDbRow};
+use crate::model::{Error, ModelManager, Result};
+use crate::pwd::{self, ContentToHash, SchemeStatus};
 use serde::{Deserialize, Serialize};
 use sqlb::{Fields, HasFields};
-use sqlx::postgres::PgRow;
 use sqlx::FromRow;
+use tracing::debug;
 use uuid::Uuid;
 
 // region:    --- User Types
@@ -21,13 +22,18 @@ pub struct UserForCreate {
 	pub pwd_clear: String,
 }
 
-#[derive(Fields)]
 pub struct UserForInsert {
 	username: String,
 	pwd: String,
 	pwd_salt: Uuid,
 }
 
+impl_data_fields!(UserForInsert {
+	username,
+	pwd,
+	pwd_salt,
+});
+
 #[derive(Clone, FromRow, Fields, Debug)]
 pub struct UserForLogin {
 	pub id: i64,
@@ -49,7 +55,7 @@ pub struct UserForAuth {
 }
 
 /// Marker trait
-pub trait UserBy: HasFields + for<'r> FromRow<'r, PgRow> + Unpin + Send {}
+pub trait UserBy: HasFields + FromDbRow {}
 
 impl UserBy for User {}
 impl UserBy for UserForCreate {}
@@ -65,32 +71,35 @@ impl DbBmc for UserBmc {
 }
 
 impl UserBmc {
-	pub async fn create<E>(
+	pub async fn create(
 		ctx: &Ctx,
 		mm: &ModelManager,
-		username: &str,
-		pwd_clear: &str,
-	) -> Result<i64>
-	where
-		E: UserBy,
-	{
-		let db = mm.db();
+		user_c: UserForCreate,
+	) -> Result<i64> {
+		let UserForCreate {
+			username,
+			pwd_clear,
+		} = user_c;
 
 		let pwd_salt = Uuid::new_v4();
 
-		let pwd = "test".to_string();
+		let pwd = pwd::hash_pwd(&ContentToHash {
+			content: pwd_clear,
+			salt: pwd_salt,
+		})?;
 
 		base::create::<Self, _>(
 			ctx,
 			mm,
 			UserForInsert {
-				username: username.to_string(),
+				username,
 				pwd,
 				pwd_salt,
 			},
 		)
 		.await
 	}
+
 	pub async fn get<E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
 	where
 		E: UserBy,
@@ -99,20 +108,18 @@ impl UserBmc {
 	}
 
 	pub async fn first_by_username<E>(
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		mm: &ModelManager,
 		username: &str,
 	) -> Result<Option<E>>
 	where
 		E: UserBy,
 	{
-		let db = mm.db();
+		let mut sql = base::select::<Self, E>(mm, ctx, None)?;
+		sql.push(" AND username = ").push_bind(username);
 
-		let user = sqlb::select()
-			.table(Self::TABLE)
-			.and_where("username", "=", username)
-			.fetch_optional::<_, E>(db)
-			.await?;
+		let mut db = mm.db().await?;
+		let user = sql.fetch_optional::<E>(&mut db).await?;
 
 		Ok(user)
 	}
@@ -123,18 +130,198 @@ impl UserBmc {
 		id: i64,
 		pwd_clear: &str,
 	) -> Result<()> {
-		let db = mm.db();
-
 		let user: UserForLogin = Self::get(ctx, mm, id).await?;
-		let pwd = "test".to_string();
+		let pwd = pwd::hash_pwd(&ContentToHash {
+			content: pwd_clear.to_string(),
+			salt: user.pwd_salt,
+		})?;
+
+		let mut fields: Vec<(&str, Bind)> = vec![("pwd", pwd.into())];
+		base::add_timestamps_for_update(&mut fields, ctx.user_id());
+		let sql = base::update_sql::<Self>(mm, ctx, id, fields);
+
+		let mut db = mm.db().await?;
+		sql.execute(&mut db).await?;
+
+		Ok(())
+	}
+
+	/// Validate the clear pwd against the user pwd,
+	/// and rehash it with the latest scheme when outdated.
+	pub async fn validate_pwd(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user: &UserForLogin,
+		pwd_clear: &str,
+	) -> Result<()> {
+		let pwd_ref = user
+			.pwd
+			.as_ref()
+			.ok_or(Error::UserHasNoPwd { user_id: user.id })?;
+
+		let scheme_status = pwd::validate_pwd(
+			&ContentToHash {
+				content: pwd_clear.to_string(),
+				salt: user.pwd_salt,
+			},
+			pwd_ref,
+		)?;
+
+		if let SchemeStatus::Outdated = scheme_status {
+			debug!("pwd encrypt scheme outdated, upgrading.");
+			Self::update_pwd(ctx, mm, user.id, pwd_clear).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Give the user a new token salt, which invalidates all the
+	/// web tokens issued so far.
+	pub async fn rotate_token_salt(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<()> {
+		let mut fields: Vec<(&str, Bind)> =
+			vec![("token_salt", Uuid::new_v4().into())];
+		base::add_timestamps_for_update(&mut fields, ctx.user_id());
+		let sql = base::update_sql::<Self>(mm, ctx, id, fields);
+
+		let mut db = mm.db().await?;
+		let count = sql.execute(&mut db).await?;
+
+		if count == 0 {
+			Err(Error::EntityNotFound {
+				entity: Self::TABLE,
+				id,
+			})
+		} else {
+			Ok(())
+		}
+	}
+
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::delete::<Self>(ctx, mm, id).await
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	async fn fx_user(mm: &ModelManager, username: &str) -> Result<UserForLogin> {
+		let ctx = Ctx::root_ctx();
+		let user_c = UserForCreate {
+			username: username.to_string(),
+			pwd_clear: "fx-pwd-01".to_string(),
+		};
+		let id = UserBmc::create(&ctx, mm, user_c).await?;
+
+		Ok(UserBmc::get(&ctx, mm, id).await?)
+	}
+
+	#[tokio::test]
+	async fn test_create_validate_pwd_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let ctx = Ctx::root_ctx();
+
+		// -- Exec
+		let user = fx_user(&mm, "fx_user_01").await?;
+		let found: Option<User> =
+			UserBmc::first_by_username(&ctx, &mm, "fx_user_01").await?;
+
+		// -- Check
+		assert_eq!(found.map(|u| u.id), Some(user.id));
+		UserBmc::validate_pwd(&ctx, &mm, &user, "fx-pwd-01").await?;
+		let res = UserBmc::validate_pwd(&ctx, &mm, &user, "fx-pwd-02").await;
+		assert!(
+			matches!(res, Err(Error::Pwd(_))),
+			"should be Pwd, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_create_err_username_taken() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		fx_user(&mm, "fx_user_01").await?;
+
+		let user_c = UserForCreate {
+			username: "fx_user_01".to_string(),
+			pwd_clear: "fx-pwd-02".to_string(),
+		};
+
+		// -- Exec
+		let res = UserBmc::create(&Ctx::root_ctx(), &mm, user_c).await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::UniqueViolation { .. })),
+			"should be UniqueViolation, was {res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_update_pwd_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let user = fx_user(&mm, "fx_user_01").await?;
+		let ctx = Ctx::new(user.id)?;
+
+		// -- Exec
+		UserBmc::update_pwd(&ctx, &mm, user.id, "fx-pwd-02").await?;
+
+		// -- Check
+		let user: UserForLogin = UserBmc::get(&ctx, &mm, user.id).await?;
+		UserBmc::validate_pwd(&ctx, &mm, &user, "fx-pwd-02").await?;
+		let res = UserBmc::validate_pwd(&ctx, &mm, &user, "fx-pwd-01").await;
+		assert!(res.is_err(), "the old pwd still validates");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rotate_token_salt_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let user = fx_user(&mm, "fx_user_01").await?;
+		let ctx = Ctx::new(user.id)?;
+
+		// -- Exec
+		UserBmc::rotate_token_salt(&ctx, &mm, user.id).await?;
+
+		// -- Check
+		let user_auth: UserForAuth = UserBmc::get(&ctx, &mm, user.id).await?;
+		assert_ne!(user_auth.token_salt, user.token_salt);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_delete_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let user = fx_user(&mm, "fx_user_01").await?;
+		let ctx = Ctx::new(user.id)?;
+
+		// -- Exec
+		UserBmc::delete(&ctx, &mm, user.id).await?;
 
-		sqlb::update()
-			.table(Self::TABLE)
-			.and_where("id", "=", id)
-			.data(vec![("pwd", pwd.to_string()).into()])
-			.exec(db)
-			.await?;
+		// -- Check
+		let res = UserBmc::get::<User>(&ctx, &mm, user.id).await;
+		assert!(
+			matches!(res, Err(Error::EntityNotFound { entity: "user", .. })),
+			"should be EntityNotFound, was {res:?}"
+		);
 
 		Ok(())
 	}
 }
+// endregion: --- Tests
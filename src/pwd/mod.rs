@@ -0,0 +1,136 @@
+// region:    --- Modules
+
+mod error;
+mod scheme;
+
+pub use self::error::{Error, Result};
+pub use scheme::SchemeStatus;
+
+use crate::pwd::scheme::{get_scheme, DEFAULT_SCHEME};
+use lazy_regex::regex_captures;
+use std::str::FromStr;
+use uuid::Uuid;
+
+// endregion: --- Modules
+
+// region:    --- Types
+
+/// The clear content to hash, with the user salt.
+pub struct ContentToHash {
+	pub content: String, // Clear content.
+	pub salt: Uuid,
+}
+
+// endregion: --- Types
+
+// region:    --- Public Functions
+
+/// Hash the pwd with the default scheme.
+/// The result is prefixed with the scheme, e.g., `#02#...`.
+pub fn hash_pwd(to_hash: &ContentToHash) -> Result<String> {
+	hash_for_scheme(DEFAULT_SCHEME, to_hash)
+}
+
+/// Validate a clear pwd against a `#_scheme_id_#...` pwd reference.
+/// Returns `SchemeStatus::Outdated` when the reference should be rehashed.
+pub fn validate_pwd(to_hash: &ContentToHash, pwd_ref: &str) -> Result<SchemeStatus> {
+	let PwdParts {
+		scheme_name,
+		hashed,
+	} = pwd_ref.parse()?;
+
+	validate_for_scheme(&scheme_name, to_hash, &hashed)?;
+
+	if scheme_name == DEFAULT_SCHEME {
+		Ok(SchemeStatus::Ok)
+	} else {
+		Ok(SchemeStatus::Outdated)
+	}
+}
+
+// endregion: --- Public Functions
+
+// region:    --- Privates
+
+fn hash_for_scheme(scheme_name: &str, to_hash: &ContentToHash) -> Result<String> {
+	let pwd_hashed = get_scheme(scheme_name)?.hash(to_hash)?;
+
+	Ok(format!("#{scheme_name}#{pwd_hashed}"))
+}
+
+fn validate_for_scheme(
+	scheme_name: &str,
+	to_hash: &ContentToHash,
+	pwd_ref: &str,
+) -> Result<()> {
+	get_scheme(scheme_name)?.validate(to_hash, pwd_ref)?;
+
+	Ok(())
+}
+
+struct PwdParts {
+	/// The scheme only (e.g., "01")
+	scheme_name: String,
+	/// The hashed password,
+	hashed: String,
+}
+
+impl FromStr for PwdParts {
+	type Err = Error;
+
+	fn from_str(pwd_with_scheme: &str) -> Result<Self> {
+		regex_captures!(r#"^#(\w+)#(.*)"#, pwd_with_scheme)
+			.map(|(_, scheme, hashed)| Self {
+				scheme_name: scheme.to_string(),
+				hashed: hashed.to_string(),
+			})
+			.ok_or(Error::PwdWithSchemeFailedParse)
+	}
+}
+
+// endregion: --- Privates
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_validate_pwd_default_scheme_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		let fx_pwd = hash_pwd(&fx_to_hash)?;
+
+		// -- Exec
+		let status = validate_pwd(&fx_to_hash, &fx_pwd)?;
+
+		// -- Check
+		assert!(fx_pwd.starts_with("#02#"));
+		assert_eq!(status, SchemeStatus::Ok);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_pwd_old_scheme_outdated() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		let fx_pwd = hash_for_scheme("01", &fx_to_hash)?;
+
+		// -- Exec
+		let status = validate_pwd(&fx_to_hash, &fx_pwd)?;
+
+		// -- Check
+		assert_eq!(status, SchemeStatus::Outdated);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,35 @@
+// region:    --- Modules
+
+mod error;
+mod scheme_01;
+mod scheme_02;
+
+pub use self::error::{Error, Result};
+
+use crate::pwd::ContentToHash;
+
+// endregion: --- Modules
+
+/// The scheme used for all new hashes.
+/// Passwords hashed with another scheme are reported as `Outdated`.
+pub const DEFAULT_SCHEME: &str = "02";
+
+#[derive(Debug, PartialEq)]
+pub enum SchemeStatus {
+	Ok,       // The pwd uses the latest scheme. All good.
+	Outdated, // The pwd uses an old scheme and should be rehashed.
+}
+
+pub trait Scheme {
+	fn hash(&self, to_hash: &ContentToHash) -> Result<String>;
+
+	fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()>;
+}
+
+pub fn get_scheme(scheme_name: &str) -> Result<Box<dyn Scheme>> {
+	match scheme_name {
+		"01" => Ok(Box::new(scheme_01::Scheme01)),
+		"02" => Ok(Box::new(scheme_02::Scheme02)),
+		_ => Err(Error::SchemeNotFound(scheme_name.to_string())),
+	}
+}
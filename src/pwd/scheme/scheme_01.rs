@@ -0,0 +1,92 @@
+use super::{Error, Result, Scheme};
+use crate::config;
+use crate::crypt::{encrypt_into_b64u, verify_b64u, EncryptContent};
+use crate::pwd::ContentToHash;
+
+/// HMAC-SHA512 of the pwd and user salt, keyed with PWD_KEY.
+pub struct Scheme01;
+
+impl Scheme for Scheme01 {
+	fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
+		encrypt_into_b64u(config().crypt.pwd_key.expose(), &enc_content(to_hash))
+			.map_err(|_| Error::Key)
+	}
+
+	/// Constant-time comparison with the reference hash.
+	fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()> {
+		let valid = verify_b64u(
+			config().crypt.pwd_key.expose(),
+			&enc_content(to_hash),
+			pwd_ref,
+		)
+		.map_err(|_| Error::Key)?;
+
+		if valid {
+			Ok(())
+		} else {
+			Err(Error::PwdValidate)
+		}
+	}
+}
+
+fn enc_content(to_hash: &ContentToHash) -> EncryptContent {
+	EncryptContent {
+		content: to_hash.content.to_string(),
+		salt: to_hash.salt.to_string(),
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use uuid::Uuid;
+
+	#[test]
+	fn test_scheme_01_hash_and_validate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+		};
+
+		// -- Exec
+		let pwd = Scheme01.hash(&fx_to_hash)?;
+
+		// -- Check
+		assert_eq!(
+			pwd,
+			Scheme01.hash(&fx_to_hash)?,
+			"hash is not deterministic"
+		);
+		Scheme01.validate(&fx_to_hash, &pwd)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_scheme_01_validate_wrong_pwd_err() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?;
+		let fx_pwd = Scheme01.hash(&ContentToHash {
+			content: "hello world".to_string(),
+			salt: fx_salt,
+		})?;
+
+		// -- Exec
+		let res = Scheme01.validate(
+			&ContentToHash {
+				content: "hello wrong".to_string(),
+				salt: fx_salt,
+			},
+			&fx_pwd,
+		);
+
+		// -- Check
+		assert!(matches!(res, Err(Error::PwdValidate)));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,107 @@
+use super::{Error, Result, Scheme};
+use crate::config;
+use crate::pwd::ContentToHash;
+use argon2::password_hash::SaltString;
+use argon2::{
+	Algorithm, Argon2, Params, PasswordHash, PasswordHasher as _,
+	PasswordVerifier as _, Version,
+};
+use std::sync::OnceLock;
+
+/// Argon2id of the pwd with the user salt, using PWD_KEY as secret.
+/// The hash is stored in the PHC string format.
+pub struct Scheme02;
+
+impl Scheme for Scheme02 {
+	fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
+		let argon2 = get_argon2()?;
+
+		let salt_b64 = SaltString::encode_b64(to_hash.salt.as_bytes())
+			.map_err(|_| Error::Salt)?;
+
+		let pwd = argon2
+			.hash_password(to_hash.content.as_bytes(), &salt_b64)
+			.map_err(|_| Error::Hash)?
+			.to_string();
+
+		Ok(pwd)
+	}
+
+	fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()> {
+		let argon2 = get_argon2()?;
+
+		let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
+
+		argon2
+			.verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
+			.map_err(|_| Error::PwdValidate)
+	}
+}
+
+fn get_argon2() -> Result<&'static Argon2<'static>> {
+	static INSTANCE: OnceLock<Option<Argon2<'static>>> = OnceLock::new();
+
+	INSTANCE
+		.get_or_init(|| {
+			Argon2::new_with_secret(
+				config().crypt.pwd_key.expose(),
+				Algorithm::Argon2id,
+				Version::V0x13,
+				Params::default(),
+			)
+			.ok()
+		})
+		.as_ref()
+		.ok_or(Error::Key)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use uuid::Uuid;
+
+	#[test]
+	fn test_scheme_02_hash_and_validate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+		};
+
+		// -- Exec
+		let pwd = Scheme02.hash(&fx_to_hash)?;
+
+		// -- Check
+		assert!(pwd.starts_with("$argon2id$"), "not a PHC argon2id hash");
+		Scheme02.validate(&fx_to_hash, &pwd)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_scheme_02_validate_wrong_pwd_err() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?;
+		let fx_pwd = Scheme02.hash(&ContentToHash {
+			content: "hello world".to_string(),
+			salt: fx_salt,
+		})?;
+
+		// -- Exec
+		let res = Scheme02.validate(
+			&ContentToHash {
+				content: "hello wrong".to_string(),
+				salt: fx_salt,
+			},
+			&fx_pwd,
+		);
+
+		// -- Check
+		assert!(matches!(res, Err(Error::PwdValidate)));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
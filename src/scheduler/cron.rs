@@ -0,0 +1,235 @@
+//! Cron expressions: `minute hour day-of-month month day-of-week`, in UTC.
+//!
+//! Each field is `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`,
+//! or a comma separated list of those. Day-of-week is 0-6 from Sunday
+//! (7 is Sunday too). As in cron, when both day fields are restricted,
+//! a day matching either one matches.
+
+use crate::scheduler::{Error, Result};
+use std::str::FromStr;
+use time::{Date, Duration, OffsetDateTime};
+
+#[derive(Debug, Clone)]
+pub struct Cron {
+	expr: String,
+	minutes: u64,
+	hours: u64,
+	days: u64,
+	months: u64,
+	weekdays: u64,
+	days_any: bool,
+	weekdays_any: bool,
+}
+
+impl FromStr for Cron {
+	type Err = Error;
+
+	fn from_str(expr: &str) -> Result<Self> {
+		let invalid = |reason: String| Error::CronInvalid {
+			expr: expr.to_string(),
+			reason,
+		};
+
+		let fields: Vec<&str> = expr.split_whitespace().collect();
+		let &[minutes, hours, days, months, weekdays] = fields.as_slice() else {
+			return Err(invalid(format!("{} fields, not 5", fields.len())));
+		};
+
+		// Sunday is 0 or 7.
+		let mut weekday_bits = parse_field(weekdays, 0, 7).map_err(invalid)?;
+		if weekday_bits & (1 << 7) != 0 {
+			weekday_bits = (weekday_bits | 1) & !(1 << 7);
+		}
+
+		Ok(Cron {
+			expr: expr.to_string(),
+			minutes: parse_field(minutes, 0, 59).map_err(invalid)?,
+			hours: parse_field(hours, 0, 23).map_err(invalid)?,
+			days: parse_field(days, 1, 31).map_err(invalid)?,
+			months: parse_field(months, 1, 12).map_err(invalid)?,
+			weekdays: weekday_bits,
+			days_any: days == "*",
+			weekdays_any: weekdays == "*",
+		})
+	}
+}
+
+impl Cron {
+	/// The first matching minute strictly after `time`, if any within
+	/// the next years (e.g., none for `0 0 30 2 *`).
+	pub fn next_after(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+		let seconds = Duration::new(time.second() as i64, time.nanosecond() as i32);
+		let mut time =
+			time.to_offset(time::UtcOffset::UTC) - seconds + Duration::minutes(1);
+		let until = time + Duration::days(5 * 366);
+
+		while time < until {
+			if !has_bit(self.months, time.month() as u8) {
+				time = first_day_of_next_month(time.date())?
+					.midnight()
+					.assume_utc();
+			} else if !self.matches_day(time) {
+				time = time.date().next_day()?.midnight().assume_utc();
+			} else if !has_bit(self.hours, time.hour()) {
+				time = time - Duration::minutes(time.minute() as i64)
+					+ Duration::hours(1);
+			} else if !has_bit(self.minutes, time.minute()) {
+				time += Duration::minutes(1);
+			} else {
+				return Some(time);
+			}
+		}
+
+		None
+	}
+
+	fn matches_day(&self, time: OffsetDateTime) -> bool {
+		let day = has_bit(self.days, time.day());
+		let weekday =
+			has_bit(self.weekdays, time.weekday().number_days_from_sunday());
+
+		if self.days_any || self.weekdays_any {
+			day && weekday
+		} else {
+			day || weekday
+		}
+	}
+}
+
+impl core::fmt::Display for Cron {
+	fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(fmt, "{}", self.expr)
+	}
+}
+
+// region:    --- Support
+/// The bits of the values the field matches.
+fn parse_field(
+	field: &str,
+	min: u32,
+	max: u32,
+) -> core::result::Result<u64, String> {
+	let mut bits = 0;
+
+	for part in field.split(',') {
+		let (range, step) = match part.split_once('/') {
+			Some((range, step)) => (range, Some(parse_value(step)?)),
+			None => (part, None),
+		};
+
+		let (lo, hi) = match range.split_once('-') {
+			_ if range == "*" => (min, max),
+			Some((lo, hi)) => (parse_value(lo)?, parse_value(hi)?),
+			// `a/n` is from `a` to the max.
+			None if step.is_some() => (parse_value(range)?, max),
+			None => {
+				let val = parse_value(range)?;
+				(val, val)
+			}
+		};
+
+		if lo < min || hi > max || lo > hi {
+			return Err(format!("'{part}' out of {min}-{max}"));
+		}
+		if step == Some(0) {
+			return Err(format!("'{part}' has a zero step"));
+		}
+
+		for val in (lo..=hi).step_by(step.unwrap_or(1) as usize) {
+			bits |= 1 << val;
+		}
+	}
+
+	Ok(bits)
+}
+
+fn parse_value(val: &str) -> core::result::Result<u32, String> {
+	val.parse().map_err(|_| format!("'{val}' is not a number"))
+}
+
+fn has_bit(bits: u64, val: u8) -> bool {
+	bits & (1 << val) != 0
+}
+
+fn first_day_of_next_month(date: Date) -> Option<Date> {
+	let (year, month) = match date.month() {
+		time::Month::December => (date.year() + 1, time::Month::January),
+		month => (date.year(), month.next()),
+	};
+
+	Date::from_calendar_date(year, month, 1).ok()
+}
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use time::format_description::well_known::Rfc3339;
+
+	fn fx_time(time: &str) -> Result<OffsetDateTime> {
+		Ok(OffsetDateTime::parse(time, &Rfc3339)?)
+	}
+
+	#[test]
+	fn test_next_after_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_cases = [
+			("* * * * *", "2026-10-19T10:15:30Z", "2026-10-19T10:16:00Z"),
+			(
+				"*/15 * * * *",
+				"2026-10-19T10:15:00Z",
+				"2026-10-19T10:30:00Z",
+			),
+			("30 3 * * *", "2026-10-19T10:15:00Z", "2026-10-20T03:30:00Z"),
+			("0 0 1 * *", "2026-12-19T10:15:00Z", "2027-01-01T00:00:00Z"),
+			// Monday (2026-10-19 is a Monday).
+			("0 9 * * 1", "2026-10-19T09:00:00Z", "2026-10-26T09:00:00Z"),
+			// Day of month or Sunday (7).
+			("0 0 28 * 7", "2026-10-19T10:15:00Z", "2026-10-25T00:00:00Z"),
+		];
+
+		for (expr, time, expected) in fx_cases {
+			// -- Exec
+			let cron: Cron = expr.parse()?;
+			let next = cron.next_after(fx_time(time)?);
+
+			// -- Check
+			assert_eq!(next, Some(fx_time(expected)?), "'{expr}' after {time}");
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_next_after_never_none() -> Result<()> {
+		// -- Setup & Fixtures
+		let cron: Cron = "0 0 30 2 *".parse()?;
+
+		// -- Exec
+		let next = cron.next_after(fx_time("2026-10-19T10:15:00Z")?);
+
+		// -- Check
+		assert_eq!(next, None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_err_invalid() -> Result<()> {
+		for expr in ["* * * *", "60 * * * *", "*/0 * * * *", "a * * * *"] {
+			// -- Exec
+			let res = expr.parse::<Cron>();
+
+			// -- Check
+			assert!(
+				matches!(res, Err(Error::CronInvalid { .. })),
+				"'{expr}' should be CronInvalid, was {res:?}"
+			);
+		}
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
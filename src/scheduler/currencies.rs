@@ -0,0 +1,21 @@
+//! Keep the currency list cache warm, so no request waits on FixedFloat
+//! for it.
+
+use crate::clients::FixedFloat;
+use crate::config;
+use crate::model::ModelManager;
+use crate::scheduler::{Error, Result};
+
+pub async fn refresh(mm: ModelManager) -> Result<()> {
+	FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await
+	.with_audit(mm, None)
+	.refresh_currencies_cache()
+	.await
+	.map_err(|ex| Error::Upstream(ex.to_string()))?;
+
+	Ok(())
+}
@@ -0,0 +1,376 @@
+//! Scheduler of the periodic jobs: runs each registered job on its cron or
+//! interval schedule, in one of two scopes (see `Scope`):
+//! - `Cluster` jobs, on one server instance at a time (advisory lock),
+//!   their last run recorded in `schedule_run`.
+//! - `Instance` jobs, on every server instance, for its in-memory state
+//!   (e.g., caches), first run at startup and not recorded.
+
+// region:    --- Modules
+
+mod cron;
+mod currencies;
+mod error;
+mod order_reconcile;
+mod rate_snapshots;
+mod retention;
+
+pub use self::cron::Cron;
+pub use self::error::{Error, Result};
+
+use crate::config;
+use crate::model::schedule_run::{ScheduleRun, ScheduleRunBmc};
+use crate::model::{self, lock_key, ModelManager};
+use crate::shutdown::{Shutdown, Workers};
+use crate::utils::now_utc;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+// endregion: --- Modules
+
+/// Longest wait between two checks of the schedules, and before checking
+/// again a job another instance is running.
+const TICK_MAX: Duration = Duration::from_secs(30);
+
+// region:    --- Schedule
+pub enum Schedule {
+	Every(Duration),
+	Cron(Cron),
+}
+
+impl Schedule {
+	/// The next run after a run at `time`, if any.
+	pub fn next_after(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+		match self {
+			Schedule::Every(interval) => Some(time + *interval),
+			Schedule::Cron(cron) => cron.next_after(time),
+		}
+	}
+}
+
+impl core::fmt::Display for Schedule {
+	fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Schedule::Every(interval) => {
+				write!(fmt, "every {}s", interval.as_secs())
+			}
+			Schedule::Cron(cron) => write!(fmt, "cron {cron}"),
+		}
+	}
+}
+// endregion: --- Schedule
+
+// region:    --- Scope
+/// Where a job runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+	/// On every instance, as it fills the in-memory state of the process.
+	/// Must never be locked cluster-wide.
+	Instance,
+	/// Once per schedule across all the instances, for the shared state
+	/// (database, upstream calls).
+	Cluster,
+}
+// endregion: --- Scope
+
+// region:    --- Registry
+type RunFn = fn(ModelManager) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+pub struct ScheduledJob {
+	pub name: &'static str,
+	pub scope: Scope,
+	pub schedule: Schedule,
+	run: RunFn,
+}
+
+/// The periodic jobs.
+pub fn registry() -> Result<Vec<ScheduledJob>> {
+	Ok(vec![
+		ScheduledJob {
+			name: "currencies_refresh",
+			// The cache is per process.
+			scope: Scope::Instance,
+			// Within the cache TTL, so it never goes stale.
+			schedule: Schedule::Every(Duration::from_secs(240)),
+			run: |mm| Box::pin(currencies::refresh(mm)),
+		},
+		ScheduledJob {
+			name: "rate_snapshots",
+			scope: Scope::Cluster,
+			schedule: Schedule::Every(Duration::from_secs(
+				config().rates.snapshot_interval_sec,
+			)),
+			run: |mm| Box::pin(rate_snapshots::capture(mm)),
+		},
+		ScheduledJob {
+			name: "order_reconcile",
+			scope: Scope::Cluster,
+			schedule: Schedule::Cron("15 * * * *".parse()?),
+			run: |mm| Box::pin(order_reconcile::enqueue(mm)),
+		},
+		ScheduledJob {
+			name: "data_retention",
+			scope: Scope::Cluster,
+			schedule: Schedule::Cron("30 3 * * *".parse()?),
+			run: |mm| Box::pin(retention::purge(mm)),
+		},
+	])
+}
+// endregion: --- Registry
+
+// region:    --- Runtime
+/// Spawn the scheduler of this server instance.
+pub fn spawn(workers: &mut Workers, mm: ModelManager) -> Result<()> {
+	let jobs = registry()?;
+	workers.spawn("scheduler", |shutdown| run(mm, jobs, shutdown));
+
+	Ok(())
+}
+
+async fn run(mm: ModelManager, jobs: Vec<ScheduledJob>, mut shutdown: Shutdown) {
+	let worker_id = format!("scheduler-{}", Uuid::new_v4());
+	info!("{:<12} - {worker_id} started", "SCHEDULER");
+
+	let mut next_runs = Vec::with_capacity(jobs.len());
+	for job in &jobs {
+		let next = match job.scope {
+			Scope::Instance => Some(now_utc()),
+			Scope::Cluster => next_run(&mm, job).await,
+		};
+		next_runs.push(next);
+	}
+
+	while !shutdown.is_triggered() {
+		for (job, next) in jobs.iter().zip(next_runs.iter_mut()) {
+			if !next.is_some_and(|next| next <= now_utc()) {
+				continue;
+			}
+
+			*next = match job.scope {
+				Scope::Instance => run_instance_job(&mm, &worker_id, job).await,
+				Scope::Cluster => run_cluster_job(&mm, &worker_id, job).await,
+			};
+		}
+
+		let wait = next_runs
+			.iter()
+			.flatten()
+			.min()
+			.and_then(|next| (*next - now_utc()).try_into().ok())
+			.unwrap_or(TICK_MAX)
+			.min(TICK_MAX);
+		tokio::select! {
+			_ = shutdown.wait() => {}
+			_ = tokio::time::sleep(wait) => {}
+		}
+	}
+}
+
+/// Run the job on this instance, returning its next run.
+async fn run_instance_job(
+	mm: &ModelManager,
+	worker_id: &str,
+	job: &ScheduledJob,
+) -> Option<OffsetDateTime> {
+	let run = exec_job(mm, worker_id, job).await;
+
+	job.schedule.next_after(run.started_at)
+}
+
+/// Run the job unless another instance is running it, or already ran it,
+/// returning its next run.
+async fn run_cluster_job(
+	mm: &ModelManager,
+	worker_id: &str,
+	job: &ScheduledJob,
+) -> Option<OffsetDateTime> {
+	let ran = match run_locked(mm, worker_id, job).await {
+		Ok(ran) => ran,
+		Err(ex) => {
+			warn!("Cannot run scheduled job '{}' - {ex}", job.name);
+			false
+		}
+	};
+
+	let next = next_run(mm, job).await;
+	// Busy (or failed to record), check again later.
+	if !ran {
+		return next.map(|next| next.max(now_utc() + TICK_MAX));
+	}
+
+	next
+}
+
+/// The next run of the job, after its last recorded run (on any instance).
+async fn next_run(mm: &ModelManager, job: &ScheduledJob) -> Option<OffsetDateTime> {
+	let last_run = match ScheduleRunBmc::get(mm, job.name).await {
+		Ok(last_run) => last_run,
+		Err(ex) => {
+			warn!("Cannot get the last run of '{}' - {ex}", job.name);
+			None
+		}
+	};
+	let after = last_run.map(|run| run.started_at).unwrap_or_else(now_utc);
+
+	job.schedule.next_after(after)
+}
+
+/// Run the cluster job under its advisory lock, and record its run.
+/// Returns whether it ran.
+async fn run_locked(
+	mm: &ModelManager,
+	worker_id: &str,
+	job: &ScheduledJob,
+) -> Result<bool> {
+	let lock = match mm.try_lock(lock_key(job.name)).await? {
+		Some(lock) => lock,
+		None => {
+			debug!("{:<12} - '{}' locked, skipped", "SCHEDULER", job.name);
+			return Ok(false);
+		}
+	};
+
+	// -- Not due anymore, when ran by another instance meanwhile.
+	if let Some(last_run) = ScheduleRunBmc::get(mm, job.name).await? {
+		let next = job.schedule.next_after(last_run.started_at);
+		if next.is_none_or(|next| next > now_utc()) {
+			lock.release().await.map_err(model::Error::from)?;
+			return Ok(false);
+		}
+	}
+
+	let run = exec_job(mm, worker_id, job).await;
+	ScheduleRunBmc::record(mm, run).await?;
+	lock.release().await.map_err(model::Error::from)?;
+
+	Ok(true)
+}
+
+/// Run the job, logging its outcome.
+async fn exec_job(
+	mm: &ModelManager,
+	worker_id: &str,
+	job: &ScheduledJob,
+) -> ScheduleRun {
+	let started_at = now_utc();
+	let start = Instant::now();
+	let res = (job.run)(mm.clone()).await;
+	let duration_ms = start.elapsed().as_millis() as i64;
+
+	let (outcome, error) = match res {
+		Ok(()) => ("ok", None),
+		Err(ex) => {
+			warn!("Scheduled job '{}' failed - {ex}", job.name);
+			("error", Some(ex.to_string()))
+		}
+	};
+	info!(
+		"{:<12} - '{}' {outcome} in {duration_ms}ms",
+		"SCHEDULER", job.name
+	);
+
+	ScheduleRun {
+		name: job.name.to_string(),
+		started_at,
+		duration_ms,
+		outcome: outcome.to_string(),
+		error,
+		run_by: worker_id.to_string(),
+	}
+}
+// endregion: --- Runtime
+
+// region:    --- Status
+#[derive(Serialize)]
+pub struct ScheduleStatus {
+	pub name: &'static str,
+	pub scope: Scope,
+	pub schedule: String,
+	/// Only recorded for the `Cluster` jobs.
+	pub last_run: Option<ScheduleRun>,
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub next_run: Option<OffsetDateTime>,
+}
+
+/// The registered jobs, with the last run of the `Cluster` ones on any
+/// instance. (The `Instance` ones run on each instance, so have none)
+pub async fn status(mm: &ModelManager) -> Result<Vec<ScheduleStatus>> {
+	let mut last_runs = ScheduleRunBmc::list(mm).await?;
+
+	let statuses = registry()?
+		.into_iter()
+		.map(|job| {
+			let last_run = last_runs
+				.iter()
+				.position(|run| run.name == job.name)
+				.filter(|_| job.scope == Scope::Cluster)
+				.map(|idx| last_runs.swap_remove(idx));
+			let after = last_run.as_ref().map(|run| run.started_at);
+			let next_run = match job.scope {
+				Scope::Instance => None,
+				Scope::Cluster => {
+					job.schedule.next_after(after.unwrap_or_else(now_utc))
+				}
+			};
+
+			ScheduleStatus {
+				name: job.name,
+				scope: job.scope,
+				schedule: job.schedule.to_string(),
+				next_run,
+				last_run,
+			}
+		})
+		.collect();
+
+	Ok(statuses)
+}
+// endregion: --- Status
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	fn fx_run(name: &str) -> ScheduleRun {
+		ScheduleRun {
+			name: name.to_string(),
+			started_at: now_utc(),
+			duration_ms: 10,
+			outcome: "ok".to_string(),
+			error: None,
+			run_by: "scheduler-fx".to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_status_scopes_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		for name in ["currencies_refresh", "data_retention"] {
+			ScheduleRunBmc::record(&mm, fx_run(name)).await?;
+		}
+
+		// -- Exec
+		let statuses = status(&mm).await?;
+
+		// -- Check
+		let find = |name: &str| statuses.iter().find(|s| s.name == name);
+		let currencies =
+			find("currencies_refresh").ok_or(anyhow::anyhow!("none"))?;
+		assert_eq!(currencies.scope, Scope::Instance);
+		assert!(currencies.last_run.is_none(), "instance job run shared");
+		let retention = find("data_retention").ok_or(anyhow::anyhow!("none"))?;
+		assert_eq!(retention.scope, Scope::Cluster);
+		assert!(retention.last_run.is_some(), "cluster job run not shared");
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
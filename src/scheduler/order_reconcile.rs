@@ -0,0 +1,14 @@
+//! Enqueue the reconciliation of the orders, so it runs (and is retried)
+//! as a job (see `jobs::order_reconcile`).
+
+use crate::ctx::Ctx;
+use crate::jobs::order_reconcile::OrderReconcile;
+use crate::model::job::JobBmc;
+use crate::model::ModelManager;
+use crate::scheduler::Result;
+
+pub async fn enqueue(mm: ModelManager) -> Result<()> {
+	JobBmc::enqueue(&Ctx::root_ctx(), &mm, OrderReconcile::job()).await?;
+
+	Ok(())
+}
@@ -0,0 +1,102 @@
+//! Capture the FixedFloat quotes of the configured pairs, for the rate
+//! history (`/api/rates/history`).
+
+use crate::clients::fixedfloat::models::ExchangeRateResponse;
+use crate::clients::FixedFloat;
+use crate::config;
+use crate::config::RatePair;
+use crate::model::rate_snapshot::{RateSnapshotBmc, RateSnapshotForCreate};
+use crate::model::ModelManager;
+use crate::scheduler::{Error, Result};
+use crate::utils::now_utc;
+use time::OffsetDateTime;
+use tracing::warn;
+
+const ORDER_TYPES: &[&str] = &["fixed", "float"];
+
+/// Capture every pair, failing (once all are tried) if any failed.
+pub async fn capture(mm: ModelManager) -> Result<()> {
+	let fixedfloat = FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await
+	.with_audit(mm.clone(), None);
+
+	let mut failed = 0;
+	for pair in config().rates.snapshot_pairs.iter() {
+		for order_type in ORDER_TYPES {
+			if let Err(ex) = capture_pair(&mm, &fixedfloat, pair, order_type).await {
+				warn!(
+					"Cannot capture {}/{} {order_type} rate - {ex}",
+					pair.from_ccy, pair.to_ccy
+				);
+				failed += 1;
+			}
+		}
+	}
+
+	if failed > 0 {
+		return Err(Error::Upstream(format!("{failed} rate capture(s) failed")));
+	}
+
+	Ok(())
+}
+
+async fn capture_pair(
+	mm: &ModelManager,
+	fixedfloat: &FixedFloat,
+	pair: &RatePair,
+	order_type: &str,
+) -> Result<()> {
+	let ctime = now_utc();
+	let price = fixedfloat
+		.get_exchange_rate(
+			order_type,
+			&pair.from_ccy,
+			&pair.to_ccy,
+			"from",
+			&pair.amount,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.map_err(|ex| Error::Upstream(ex.to_string()))?;
+
+	let snapshot_c = snapshot_for_create(ctime, pair, order_type, &price)?;
+	RateSnapshotBmc::create(mm, snapshot_c).await?;
+
+	Ok(())
+}
+
+/// The rate is `from.rate` (`to_ccy` per `from_ccy`), the limits and USD
+/// value are the `from` ones.
+fn snapshot_for_create(
+	ctime: OffsetDateTime,
+	pair: &RatePair,
+	order_type: &str,
+	price: &ExchangeRateResponse,
+) -> Result<RateSnapshotForCreate> {
+	let from = &price.data.from;
+	let parse = |val: &Option<String>| -> Option<f64> {
+		val.as_deref().and_then(|val| val.parse().ok())
+	};
+
+	let rate = parse(&from.rate).ok_or_else(|| {
+		Error::Upstream(format!("no rate in the {order_type} price response"))
+	})?;
+
+	Ok(RateSnapshotForCreate {
+		ctime,
+		order_type: order_type.to_string(),
+		from_ccy: pair.from_ccy.to_string(),
+		to_ccy: pair.to_ccy.to_string(),
+		amount: pair.amount.to_string(),
+		rate,
+		min_amount: parse(&from.min),
+		max_amount: parse(&from.max),
+		usd: parse(&from.usd),
+	})
+}
@@ -0,0 +1,38 @@
+//! Delete the audit and log rows, the rate snapshots, and the done jobs,
+//! past their retention.
+
+use crate::model::job::JobBmc;
+use crate::model::rate_snapshot::RateSnapshotBmc;
+use crate::model::request_log::RequestLogBmc;
+use crate::model::upstream_call::UpstreamCallBmc;
+use crate::model::ModelManager;
+use crate::scheduler::Result;
+use crate::utils::now_utc;
+use time::Duration;
+use tracing::info;
+
+const UPSTREAM_CALL_RETENTION: Duration = Duration::days(30);
+const REQUEST_LOG_RETENTION: Duration = Duration::days(30);
+const DONE_JOB_RETENTION: Duration = Duration::days(7);
+/// Covers the longest rate history (1000 daily intervals).
+const RATE_SNAPSHOT_RETENTION: Duration = Duration::days(1000);
+
+pub async fn purge(mm: ModelManager) -> Result<()> {
+	let now = now_utc();
+
+	let calls =
+		UpstreamCallBmc::delete_before(&mm, now - UPSTREAM_CALL_RETENTION).await?;
+	let lines =
+		RequestLogBmc::delete_before(&mm, now - REQUEST_LOG_RETENTION).await?;
+	let snapshots =
+		RateSnapshotBmc::delete_before(&mm, now - RATE_SNAPSHOT_RETENTION).await?;
+	let jobs = JobBmc::delete_done_before(&mm, now - DONE_JOB_RETENTION).await?;
+
+	info!(
+		"{:<12} - purged {calls} upstream calls, {lines} request log lines, \
+		{snapshots} rate snapshots, {jobs} done jobs",
+		"RETENTION"
+	);
+
+	Ok(())
+}
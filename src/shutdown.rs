@@ -0,0 +1,112 @@
+//! Shutdown signal, and the background workers stopped on it.
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout_at, Instant};
+use tracing::{info, warn};
+
+/// Resolves on SIGINT (Ctrl-C) or SIGTERM.
+pub async fn signal() {
+	let ctrl_c = async {
+		if let Err(ex) = tokio::signal::ctrl_c().await {
+			warn!("Cannot listen for Ctrl-C - {ex:?}");
+			std::future::pending::<()>().await;
+		}
+	};
+
+	#[cfg(unix)]
+	let terminate = async {
+		use tokio::signal::unix::{signal, SignalKind};
+
+		match signal(SignalKind::terminate()) {
+			Ok(mut sigterm) => {
+				sigterm.recv().await;
+			}
+			Err(ex) => {
+				warn!("Cannot listen for SIGTERM - {ex:?}");
+				std::future::pending::<()>().await;
+			}
+		}
+	};
+	#[cfg(not(unix))]
+	let terminate = std::future::pending::<()>();
+
+	tokio::select! {
+		_ = ctrl_c => {},
+		_ = terminate => {},
+	}
+
+	info!("{:<12} - signal received, draining requests", "SHUTDOWN");
+}
+
+// region:    --- Shutdown
+/// Handed to each worker, to know when to stop.
+#[derive(Clone)]
+pub struct Shutdown {
+	rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+	pub fn is_triggered(&self) -> bool {
+		*self.rx.borrow()
+	}
+
+	/// Resolves once shutdown is triggered.
+	pub async fn wait(&mut self) {
+		while !*self.rx.borrow() {
+			if self.rx.changed().await.is_err() {
+				return;
+			}
+		}
+	}
+}
+// endregion: --- Shutdown
+
+// region:    --- Workers
+/// The background workers, stopped together after the server has drained.
+pub struct Workers {
+	tx: watch::Sender<bool>,
+	rx: watch::Receiver<bool>,
+	handles: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Workers {
+	pub fn new() -> Self {
+		let (tx, rx) = watch::channel(false);
+
+		Workers {
+			tx,
+			rx,
+			handles: Vec::new(),
+		}
+	}
+
+	/// Spawn a worker. It must return soon after its `Shutdown` triggers.
+	pub fn spawn<F, Fut>(&mut self, name: &'static str, worker: F)
+	where
+		F: FnOnce(Shutdown) -> Fut,
+		Fut: Future<Output = ()> + Send + 'static,
+	{
+		let shutdown = Shutdown {
+			rx: self.rx.clone(),
+		};
+		self.handles.push((name, tokio::spawn(worker(shutdown))));
+	}
+
+	/// Trigger the shutdown, and wait up to `timeout` for all the workers.
+	pub async fn shutdown(self, timeout: Duration) {
+		let _ = self.tx.send(true);
+
+		let deadline = Instant::now() + timeout;
+		for (name, handle) in self.handles {
+			match timeout_at(deadline, handle).await {
+				Ok(Ok(())) => info!("{:<12} - worker '{name}' stopped", "SHUTDOWN"),
+				Ok(Err(ex)) => warn!("Worker '{name}' failed - {ex:?}"),
+				Err(_) => warn!("Worker '{name}' did not stop in {timeout:?}"),
+			}
+		}
+	}
+}
+// endregion: --- Workers
@@ -1,5 +1,9 @@
 // region:    --- Modules
 
+mod secret;
+
+pub use secret::Secret;
+
 use anyhow::Result;
 use time::format_description::well_known::Rfc3339;
 use time::{Duration, OffsetDateTime};
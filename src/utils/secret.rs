@@ -0,0 +1,71 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Holds a secret value (key, password, token).
+///
+/// `Debug`, `Display` and `Serialize` never show the value,
+/// which has to be read explicitly with `expose()`.
+/// The value is zeroized on drop.
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+	pub fn new(value: T) -> Self {
+		Secret(value)
+	}
+
+	pub fn expose(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+	fn from(value: T) -> Self {
+		Secret(value)
+	}
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+	fn drop(&mut self) {
+		self.0.zeroize();
+	}
+}
+
+impl<T: Zeroize> core::fmt::Debug for Secret<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(REDACTED)
+	}
+}
+
+impl<T: Zeroize> core::fmt::Display for Secret<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(REDACTED)
+	}
+}
+
+impl<T: Zeroize> Serialize for Secret<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(REDACTED)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for Secret<T>
+where
+	T: Zeroize + Deserialize<'de>,
+{
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		T::deserialize(deserializer).map(Secret)
+	}
+}
+
+/// Lets `#[derive(sqlb::Fields)]` entities hold a secret column.
+impl sqlb::SqlxBindable for Secret<String> {
+	fn bind_query<'q>(
+		&'q self,
+		query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+	) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+		query.bind(self.0.as_str())
+	}
+}
@@ -1,18 +1,40 @@
+use crate::clients::fixedfloat;
+use crate::crypt;
+use crate::model;
+use crate::web::mw_auth::CtxExtError;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
 use axum::response::{IntoResponse, Response};
 use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::debug;
 
 // Make our own error that wraps `anyhow::Error`.
 #[derive(Debug)]
 pub struct AppError(anyhow::Error);
 
 // Tell axum how to convert `AppError` into a response.
+// The client facing body is built by `mw_response_map`.
 impl IntoResponse for AppError {
 	fn into_response(self) -> Response {
-		(
-			StatusCode::INTERNAL_SERVER_ERROR,
-			format!("Something went wrong: {}", self.0),
-		)
-			.into_response()
+		debug!("{:<12} - app_error - {self:?}", "INTO_RES");
+
+		// Create a placeholder Axum reponse.
+		let mut response = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+		// Insert the error into the reponse.
+		response.extensions_mut().insert(Arc::new(self));
+
+		response
+	}
+}
+
+impl core::fmt::Display for AppError {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{}", self.0)
 	}
 }
 
@@ -24,3 +46,377 @@ where
 		Self(err.into())
 	}
 }
+
+// region:    --- Web Error
+/// Typed errors raised by the web layer itself.
+/// (carried through `AppError` as an `anyhow::Error`)
+#[derive(Debug, Serialize)]
+pub enum Error {
+	// -- ReqStamp
+	ReqStampNotInReqExt,
+
+	// -- Params
+	InvalidDirection { direction: String },
+	SlippageInvalid { slippage_pct: f64, max_pct: f64 },
+	ListParamsInvalid { reason: String },
+	RateHistoryParamsInvalid { reason: String },
+
+	// -- Login
+	LoginFailUsernameNotFound,
+	LoginFailUserHasNoPwd { user_id: i64 },
+	LoginFailPwdNotMatching { user_id: i64 },
+
+	// -- User
+	UserUsernameInvalid { reason: &'static str },
+	UserUsernameTaken { username: String },
+	UserPwdTooWeak { reason: &'static str },
+	UserPwdNotMatching,
+
+	// -- CtxExtError
+	CtxExt(CtxExtError),
+	AdminRequired,
+	ScrapeTokenInvalid,
+
+	// -- Order
+	OrderNotFound { ff_id: String },
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
+// endregion: --- Web Error
+
+// region:    --- Client Error
+
+/// From the root error to the http status code and ClientError.
+impl AppError {
+	pub fn client_status_and_error(&self) -> (StatusCode, ClientError) {
+		if let Some(web_error) = self.0.downcast_ref::<Error>() {
+			return web_error.client_status_and_error();
+		}
+
+		if let Some(crypt_error) = self.0.downcast_ref::<crypt::Error>() {
+			return crypt_client_status_and_error(crypt_error);
+		}
+
+		if let Some(ff_error) = self.0.downcast_ref::<fixedfloat::Error>() {
+			return fixedfloat_client_status_and_error(ff_error);
+		}
+
+		if let Some(model_error) = self.0.downcast_ref::<model::Error>() {
+			return model_client_status_and_error(model_error);
+		}
+
+		// -- Extractor rejections (see `web::extract`), as bad params.
+		if let Some(rejection) = self.0.downcast_ref::<JsonRejection>() {
+			return rejection_status_and_error(rejection.status(), rejection);
+		}
+		if let Some(rejection) = self.0.downcast_ref::<QueryRejection>() {
+			return rejection_status_and_error(rejection.status(), rejection);
+		}
+
+		// -- Fallback.
+		(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			ClientError::SERVICE_ERROR,
+		)
+	}
+}
+
+impl Error {
+	fn client_status_and_error(&self) -> (StatusCode, ClientError) {
+		use Error::*;
+
+		match self {
+			ReqStampNotInReqExt => (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				ClientError::SERVICE_ERROR,
+			),
+
+			// -- Params
+			InvalidDirection { direction } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					reason: format!("invalid direction '{direction}'"),
+				},
+			),
+			SlippageInvalid {
+				slippage_pct,
+				max_pct,
+			} => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					reason: format!(
+						"slippagePct {slippage_pct} not in (0, {max_pct}]"
+					),
+				},
+			),
+			ListParamsInvalid { reason } | RateHistoryParamsInvalid { reason } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					reason: reason.to_string(),
+				},
+			),
+
+			// -- Login / Auth
+			LoginFailUsernameNotFound
+			| LoginFailUserHasNoPwd { .. }
+			| LoginFailPwdNotMatching { .. } => {
+				(StatusCode::UNAUTHORIZED, ClientError::LOGIN_FAIL)
+			}
+			CtxExt(_) | ScrapeTokenInvalid => {
+				(StatusCode::UNAUTHORIZED, ClientError::NO_AUTH)
+			}
+			AdminRequired => (StatusCode::FORBIDDEN, ClientError::FORBIDDEN),
+
+			// -- User
+			UserUsernameInvalid { reason } | UserPwdTooWeak { reason } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					reason: reason.to_string(),
+				},
+			),
+			UserUsernameTaken { username } => (
+				StatusCode::CONFLICT,
+				ClientError::USERNAME_TAKEN {
+					username: username.to_string(),
+				},
+			),
+			UserPwdNotMatching => {
+				(StatusCode::UNAUTHORIZED, ClientError::PWD_NOT_MATCHING)
+			}
+
+			// -- Order
+			OrderNotFound { ff_id } => (
+				StatusCode::NOT_FOUND,
+				ClientError::ENTITY_NOT_FOUND {
+					entity: "order",
+					id: ff_id.to_string(),
+				},
+			),
+		}
+	}
+}
+
+fn rejection_status_and_error(
+	status: StatusCode,
+	rejection: &impl std::error::Error,
+) -> (StatusCode, ClientError) {
+	(
+		status,
+		ClientError::INVALID_PARAMS {
+			reason: rejection.to_string(),
+		},
+	)
+}
+
+fn crypt_client_status_and_error(
+	crypt_error: &crypt::Error,
+) -> (StatusCode, ClientError) {
+	use crypt::Error::*;
+
+	match crypt_error {
+		QuoteInvalidFormat
+		| QuoteCannotDecode
+		| QuoteSignatureNotMatching
+		| QuoteExpNotIso
+		| QuoteInvalidAmount => (StatusCode::BAD_REQUEST, ClientError::QUOTE_INVALID),
+		QuoteExpired => {
+			(StatusCode::UNPROCESSABLE_ENTITY, ClientError::QUOTE_EXPIRED)
+		}
+		QuotePairMismatch | QuoteOrderMismatch => (
+			StatusCode::UNPROCESSABLE_ENTITY,
+			ClientError::QUOTE_MISMATCH,
+		),
+		QuoteSlippageExceeded {
+			quoted,
+			fresh,
+			slippage_pct,
+		} => (
+			StatusCode::UNPROCESSABLE_ENTITY,
+			ClientError::SLIPPAGE_EXCEEDED {
+				quoted: quoted.to_string(),
+				fresh: fresh.to_string(),
+				slippage_pct: *slippage_pct,
+			},
+		),
+
+		// -- Key and web token errors are service side issues.
+		_ => (
+			StatusCode::INTERNAL_SERVER_ERROR,
+			ClientError::SERVICE_ERROR,
+		),
+	}
+}
+
+fn fixedfloat_client_status_and_error(
+	ff_error: &fixedfloat::Error,
+) -> (StatusCode, ClientError) {
+	use fixedfloat::Error::*;
+
+	match ff_error {
+		Rejected { code, msg, .. } => (
+			StatusCode::UNPROCESSABLE_ENTITY,
+			ClientError::UPSTREAM_REJECTED {
+				code: *code,
+				msg: msg.to_string(),
+			},
+		),
+		RateLimited => (
+			StatusCode::TOO_MANY_REQUESTS,
+			ClientError::UPSTREAM_RATE_LIMITED,
+		),
+		BadResponse { .. } => {
+			(StatusCode::BAD_GATEWAY, ClientError::UPSTREAM_BAD_RESPONSE)
+		}
+		Unavailable(_) => (
+			StatusCode::SERVICE_UNAVAILABLE,
+			ClientError::UPSTREAM_UNAVAILABLE,
+		),
+		FailToSign(_) => (
+			StatusCode::INTERNAL_SERVER_ERROR,
+			ClientError::SERVICE_ERROR,
+		),
+	}
+}
+
+fn model_client_status_and_error(
+	model_error: &model::Error,
+) -> (StatusCode, ClientError) {
+	use model::Error::*;
+
+	match model_error {
+		EntityNotFound { entity, id } => (
+			StatusCode::NOT_FOUND,
+			ClientError::ENTITY_NOT_FOUND {
+				entity,
+				id: id.to_string(),
+			},
+		),
+		UniqueViolation { table, .. } => (
+			StatusCode::CONFLICT,
+			ClientError::ENTITY_CONFLICT {
+				entity: table.to_string(),
+			},
+		),
+		VersionConflict { entity, id, .. } => (
+			StatusCode::CONFLICT,
+			ClientError::ENTITY_VERSION_CONFLICT {
+				entity,
+				id: id.to_string(),
+			},
+		),
+		JobNotRetryable { .. } => (
+			StatusCode::CONFLICT,
+			ClientError::ENTITY_CONFLICT {
+				entity: "job".to_string(),
+			},
+		),
+		ListFieldUnknown { field } => (
+			StatusCode::BAD_REQUEST,
+			ClientError::INVALID_PARAMS {
+				reason: format!("unknown list field '{field}'"),
+			},
+		),
+		ListValueInvalid {
+			field,
+			value,
+			expected,
+		} => (
+			StatusCode::BAD_REQUEST,
+			ClientError::INVALID_PARAMS {
+				reason: format!("'{field}' expects {expected}, not {value}"),
+			},
+		),
+
+		// -- Database unreachable or saturated.
+		Store(
+			model::StoreError::FailToCreatePool(_)
+			| model::StoreError::FailToAcquireConn(_),
+		)
+		| Sqlx(
+			sqlx::Error::PoolTimedOut
+			| sqlx::Error::PoolClosed
+			| sqlx::Error::Io(_)
+			| sqlx::Error::Tls(_),
+		) => (
+			StatusCode::SERVICE_UNAVAILABLE,
+			ClientError::SERVICE_UNAVAILABLE,
+		),
+
+		VersionNotSupported { .. }
+		| UserHasNoPwd { .. }
+		| Pwd(_)
+		| Store(_)
+		| Sqlx(_) => (
+			StatusCode::INTERNAL_SERVER_ERROR,
+			ClientError::SERVICE_ERROR,
+		),
+	}
+}
+
+/// The error `type` and `detail` sent to the client.
+/// Never carries server internals.
+#[derive(Debug, Serialize, strum_macros::AsRefStr)]
+#[serde(tag = "type", content = "detail")]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum ClientError {
+	// -- Our validations
+	INVALID_PARAMS {
+		reason: String,
+	},
+	QUOTE_INVALID,
+	QUOTE_EXPIRED,
+	QUOTE_MISMATCH,
+	SLIPPAGE_EXCEEDED {
+		quoted: String,
+		fresh: String,
+		slippage_pct: f64,
+	},
+
+	// -- Auth
+	LOGIN_FAIL,
+	NO_AUTH,
+	FORBIDDEN,
+	PWD_NOT_MATCHING,
+
+	// -- Entities
+	ENTITY_NOT_FOUND {
+		entity: &'static str,
+		id: String,
+	},
+	ENTITY_CONFLICT {
+		entity: String,
+	},
+	/// The entity was updated since it was read. Re-read it and retry.
+	ENTITY_VERSION_CONFLICT {
+		entity: &'static str,
+		id: String,
+	},
+	USERNAME_TAKEN {
+		username: String,
+	},
+
+	// -- Upstream FixedFloat
+	UPSTREAM_REJECTED {
+		code: i32,
+		msg: String,
+	},
+	UPSTREAM_RATE_LIMITED,
+	UPSTREAM_BAD_RESPONSE,
+	UPSTREAM_UNAVAILABLE,
+
+	// -- Internal
+	SERVICE_ERROR,
+	SERVICE_UNAVAILABLE,
+}
+// endregion: --- Client Error
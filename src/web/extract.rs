@@ -0,0 +1,96 @@
+//! The `Json` and `Query` extractors, rejecting as `AppError`, so bad
+//! bodies and query strings get the client error body (see `mw_res_map`).
+
+use crate::web::error::AppError;
+use axum::extract::FromRequest;
+use axum::extract::FromRequestParts;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+/// `axum::Json`, rejecting as `AppError`.
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+	fn into_response(self) -> Response {
+		axum::Json(self.0).into_response()
+	}
+}
+
+/// `axum::extract::Query`, rejecting as `AppError`.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct Query<T>(pub T);
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::web::{mw_req_stamp, mw_res_map};
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::header::CONTENT_TYPE;
+	use axum::http::{Request, StatusCode};
+	use axum::routing::post;
+	use axum::{middleware, Router};
+	use serde::Deserialize;
+	use serde_json::Value;
+	use tower::ServiceExt;
+
+	#[derive(Deserialize)]
+	struct FxBody {
+		#[allow(unused)]
+		amount: String,
+	}
+
+	async fn fx_handler(Json(_body): Json<FxBody>) -> Json<&'static str> {
+		Json("ok")
+	}
+
+	fn fx_routes() -> Router {
+		Router::new()
+			.route("/fx", post(fx_handler))
+			.layer(middleware::map_response(mw_res_map::mw_response_map))
+			.layer(middleware::from_fn(mw_req_stamp::mw_req_stamp))
+	}
+
+	#[tokio::test]
+	async fn test_json_err_client_error_body() -> Result<()> {
+		// -- Exec & Check
+		// Malformed JSON, then a missing field.
+		let fx_bodies = [
+			(r#"{"amount": "1""#, StatusCode::BAD_REQUEST),
+			(r#"{"amt": "1"}"#, StatusCode::UNPROCESSABLE_ENTITY),
+		];
+		for (body, status) in fx_bodies {
+			let req = Request::post("/fx")
+				.header(CONTENT_TYPE, "application/json")
+				.body(Body::from(body))?;
+			let res = fx_routes().oneshot(req).await?;
+
+			assert_eq!(res.status(), status, "{body}");
+			let res_body = hyper::body::to_bytes(res.into_body()).await?;
+			let res_body: Value = serde_json::from_slice(&res_body)?;
+			assert_eq!(res_body["error"]["type"], "INVALID_PARAMS", "{body}");
+			assert!(res_body["error"]["req_uuid"].is_string());
+		}
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_json_err_content_type() -> Result<()> {
+		// -- Setup & Fixtures
+		let req = Request::post("/fx").body(Body::from(r#"{"amount": "1"}"#))?;
+
+		// -- Exec
+		let res = fx_routes().oneshot(req).await?;
+
+		// -- Check
+		assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
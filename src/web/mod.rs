@@ -1,7 +1,218 @@
 // region:    --- Modules
 pub mod error;
+pub mod extract;
 mod models;
+pub mod mw_auth;
+pub mod mw_metrics;
+pub mod mw_req_stamp;
+pub mod mw_res_map;
 
 pub mod routes;
 
-use tower_cookies::{Cookie, Cookies};
+use crate::crypt::token::generate_web_token;
+use crate::model::ModelManager;
+use crate::Result;
+use axum::{middleware, Router};
+use tower_cookies::{Cookie, CookieManagerLayer, Cookies};
+
+// endregion: --- Modules
+
+pub const AUTH_TOKEN: &str = "auth-token";
+
+/// All the routes of the server, with their middlewares, and the web
+/// folder as fallback.
+pub fn routes_all(mm: ModelManager) -> Router {
+	Router::new()
+		.merge(routes::fixedfloat::routes(mm.clone()))
+		.merge(routes::utils::routes())
+		.merge(routes::login::routes(mm.clone()))
+		.merge(routes::users::routes(mm.clone()))
+		.merge(routes::admin::routes(mm.clone()))
+		.merge(routes::rates::routes(mm.clone()))
+		.merge(routes::metrics::routes(mm.clone()))
+		.merge(routes::health::routes(mm.clone()))
+		.layer(middleware::map_response(mw_res_map::mw_response_map))
+		.layer(middleware::from_fn_with_state(mm, mw_auth::mw_ctx_resolve))
+		.layer(CookieManagerLayer::new())
+		.layer(middleware::from_fn(mw_req_stamp::mw_req_stamp))
+		.layer(middleware::from_fn(mw_metrics::mw_metrics))
+		.fallback_service(routes::static_files::serve_dir())
+}
+
+fn set_token_cookie(cookies: &Cookies, user: &str, salt: &str) -> Result<()> {
+	let token = generate_web_token(user, salt)?;
+
+	let mut cookie = Cookie::new(AUTH_TOKEN, token.to_string());
+	cookie.set_http_only(true);
+	cookie.set_path("/");
+
+	cookies.add(cookie);
+
+	Ok(())
+}
+
+fn remove_token_cookie(cookies: &Cookies) -> Result<()> {
+	let mut cookie = Cookie::named(AUTH_TOKEN);
+	cookie.set_path("/");
+
+	cookies.remove(cookie);
+
+	Ok(())
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ctx::Ctx;
+	use crate::model::job::{JobBmc, JobForCreate};
+	use crate::model::order::{OrderBmc, OrderForCreate};
+	use crate::model::user::{User, UserBmc};
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::header::{CONTENT_TYPE, COOKIE, SET_COOKIE};
+	use axum::http::{Request, StatusCode};
+	use serde_json::{json, Value};
+	use tower::ServiceExt;
+
+	// -- Setup & Fixtures
+
+	/// Run `req` through all the routes, returning the status and the
+	/// JSON body.
+	async fn fx_call(
+		mm: &ModelManager,
+		req: Request<Body>,
+	) -> Result<(StatusCode, Value)> {
+		let res = routes_all(mm.clone()).oneshot(req).await?;
+		let status = res.status();
+		let body = hyper::body::to_bytes(res.into_body()).await?;
+
+		Ok((status, serde_json::from_slice(&body)?))
+	}
+
+	/// Login as the seed user `demo`, returning the auth-token cookie.
+	async fn fx_login(mm: &ModelManager) -> Result<String> {
+		let req = Request::post("/api/login")
+			.header(CONTENT_TYPE, "application/json")
+			.body(Body::from(
+				json!({ "username": "demo", "pwd": "demo" }).to_string(),
+			))?;
+		let res = routes_all(mm.clone()).oneshot(req).await?;
+		assert_eq!(res.status(), StatusCode::OK);
+
+		let cookie = res
+			.headers()
+			.get_all(SET_COOKIE)
+			.iter()
+			.filter_map(|val| val.to_str().ok())
+			.find(|val| val.starts_with(AUTH_TOKEN))
+			.and_then(|val| val.split(';').next())
+			.ok_or(anyhow::anyhow!("no auth-token cookie"))?;
+
+		Ok(cookie.to_string())
+	}
+
+	fn fx_get(uri: &str, cookie: Option<&str>) -> Result<Request<Body>> {
+		let mut req = Request::get(uri);
+		if let Some(cookie) = cookie {
+			req = req.header(COOKIE, cookie);
+		}
+
+		Ok(req.body(Body::empty())?)
+	}
+
+	#[tokio::test]
+	async fn test_login_orders_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+		let order_c = OrderForCreate {
+			ff_id: "FX01".to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: "NEW".to_string(),
+		};
+		OrderBmc::create(&Ctx::new(demo.id)?, &mm, order_c).await?;
+
+		// -- Exec
+		let cookie = fx_login(&mm).await?;
+		let (status, body) =
+			fx_call(&mm, fx_get("/api/orders", Some(&cookie))?).await?;
+
+		// -- Check
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body["total"], 1);
+		assert_eq!(body["data"][0]["ff_id"], "FX01");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_orders_err_no_auth() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+
+		// -- Exec
+		let (status, body) = fx_call(&mm, fx_get("/api/orders", None)?).await?;
+
+		// -- Check
+		assert_eq!(status, StatusCode::UNAUTHORIZED);
+		assert_eq!(body["error"]["type"], "NO_AUTH");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_login_err_pwd() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let req = Request::post("/api/login")
+			.header(CONTENT_TYPE, "application/json")
+			.body(Body::from(
+				json!({ "username": "demo", "pwd": "wrong" }).to_string(),
+			))?;
+
+		// -- Exec
+		let (status, body) = fx_call(&mm, req).await?;
+
+		// -- Check
+		assert_eq!(status, StatusCode::UNAUTHORIZED);
+		assert_eq!(body["error"]["type"], "LOGIN_FAIL");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_admin_jobs_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let job_c = JobForCreate {
+			kind: "fx_kind".to_string(),
+			payload: json!({}),
+			run_at: None,
+			max_attempts: None,
+		};
+		let id = JobBmc::enqueue(&Ctx::root_ctx(), &mm, job_c).await?;
+
+		// -- Exec
+		// `demo` is an admin (`SERVICE_ADMIN_USERNAMES`).
+		let cookie = fx_login(&mm).await?;
+		let uri = "/api/admin/jobs?kind=fx_kind";
+		let (status, body) = fx_call(&mm, fx_get(uri, Some(&cookie))?).await?;
+
+		// -- Check
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(body["data"][0]["id"], id);
+		assert_eq!(body["data"][0]["status"], "pending");
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -1,4 +1,12 @@
+use crate::model::filter::{Filter, ListOptions};
+use crate::model::rate_snapshot::RateHistoryFilter;
+use crate::utils::{now_utc, Secret};
+use crate::web::error::Error;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ExchangeRateRequest {
@@ -14,10 +22,158 @@ pub struct CreateOrderRequest {
 	pub amount: String,
 	#[serde(rename = "toAddress")]
 	pub to_address: String,
+	/// Signed quote token returned by `/api/exchange-rate`.
+	pub quote: Option<String>,
+	/// Max accepted move of the quoted amount, in percent.
+	#[serde(rename = "slippagePct")]
+	pub slippage_pct: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OrderDetailsRequest {
 	pub id: String,
-	pub token: String,
+	pub token: Secret<String>,
 }
+
+/// Query params of the list endpoints, each a JSON string.
+/// e.g., `?filter={"status":{"$eq":"NEW"}}&list_options={"limit":20}`
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+	pub filter: Option<String>,
+	pub list_options: Option<String>,
+}
+
+impl ListParams {
+	pub fn parse(&self) -> Result<(Option<Filter>, Option<ListOptions>), Error> {
+		let filter = parse_json_param("filter", self.filter.as_deref())?;
+		let list_options =
+			parse_json_param("list_options", self.list_options.as_deref())?;
+
+		Ok((filter, list_options))
+	}
+}
+
+fn parse_json_param<T: DeserializeOwned>(
+	name: &'static str,
+	val: Option<&str>,
+) -> Result<Option<T>, Error> {
+	val.map(serde_json::from_str::<T>)
+		.transpose()
+		.map_err(|ex| Error::ListParamsInvalid {
+			reason: format!("{name}: {ex}"),
+		})
+}
+
+/// Query params of `/api/rates/history`.
+/// e.g., `?pair=BTC/BTCLN&from=2026-10-01T00:00:00Z&interval=1h`
+#[derive(Debug, Deserialize)]
+pub struct RateHistoryParams {
+	/// `FROM/TO`, as in `rates.snapshot_pairs`.
+	pub pair: String,
+	/// rfc3339, 24 hours before `to` by default.
+	pub from: Option<String>,
+	/// rfc3339 (excluded), now by default.
+	pub to: Option<String>,
+	/// `5m`, `15m`, `1h` (default), `4h` or `1d`.
+	pub interval: Option<String>,
+	/// `fixed` (default) or `float`.
+	#[serde(rename = "type")]
+	pub order_type: Option<String>,
+}
+
+impl RateHistoryParams {
+	/// Most intervals in one response.
+	const MAX_INTERVALS: i64 = 1000;
+
+	pub fn parse(self) -> Result<(RateHistoryFilter, Duration), Error> {
+		let invalid = |reason: String| Error::RateHistoryParamsInvalid { reason };
+
+		let (from_ccy, to_ccy) = self
+			.pair
+			.split_once('/')
+			.filter(|(from, to)| !from.is_empty() && !to.is_empty())
+			.ok_or_else(|| {
+				invalid(format!("pair '{}' is not FROM/TO", self.pair))
+			})?;
+
+		let interval = match self.interval.as_deref().unwrap_or("1h") {
+			"5m" => Duration::from_secs(5 * 60),
+			"15m" => Duration::from_secs(15 * 60),
+			"1h" => Duration::from_secs(3600),
+			"4h" => Duration::from_secs(4 * 3600),
+			"1d" => Duration::from_secs(24 * 3600),
+			other => {
+				return Err(invalid(format!("interval '{other}' not supported")))
+			}
+		};
+
+		let order_type = self.order_type.as_deref().unwrap_or("fixed");
+		if !["fixed", "float"].contains(&order_type) {
+			return Err(invalid(format!("type '{order_type}' not supported")));
+		}
+
+		// In UTC, as the stored snapshot times (and their comparisons).
+		let parse_time = |name: &str, val: &str| {
+			OffsetDateTime::parse(val, &Rfc3339)
+				.map(|time| time.to_offset(UtcOffset::UTC))
+				.map_err(|_| invalid(format!("{name} '{val}' is not rfc3339")))
+		};
+		let to = match self.to.as_deref() {
+			Some(to) => parse_time("to", to)?,
+			None => now_utc(),
+		};
+		let from = match self.from.as_deref() {
+			Some(from) => parse_time("from", from)?,
+			None => to - time::Duration::days(1),
+		};
+
+		let intervals = (to - from).whole_seconds() / interval.as_secs() as i64;
+		if from >= to || intervals > Self::MAX_INTERVALS {
+			return Err(invalid(format!(
+				"from must be before to, within {} intervals",
+				Self::MAX_INTERVALS
+			)));
+		}
+
+		let filter = RateHistoryFilter {
+			from_ccy: from_ccy.to_string(),
+			to_ccy: to_ccy.to_string(),
+			order_type: order_type.to_string(),
+			from,
+			to,
+		};
+
+		Ok((filter, interval))
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_rate_history_params_parse_utc_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let params = RateHistoryParams {
+			pair: "BTC/BTCLN".to_string(),
+			from: Some("2026-10-19T12:00:00+02:00".to_string()),
+			to: Some("2026-10-19T08:00:00-04:00".to_string()),
+			interval: None,
+			order_type: None,
+		};
+
+		// -- Exec
+		let (filter, _) = params.parse()?;
+
+		// -- Check
+		assert_eq!(filter.from.offset(), UtcOffset::UTC);
+		assert_eq!(filter.to.offset(), UtcOffset::UTC);
+		assert_eq!(filter.from.hour(), 10);
+		assert_eq!(filter.to.hour(), 12);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
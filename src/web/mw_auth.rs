@@ -0,0 +1,180 @@
+use crate::config;
+use crate::crypt;
+use crate::crypt::token::{validate_web_token, Token};
+use crate::ctx::Ctx;
+use crate::model::user::{User, UserBmc, UserForAuth};
+use crate::model::ModelManager;
+use crate::utils::Secret;
+use crate::web::error::{AppError, Error};
+use crate::web::{remove_token_cookie, set_token_cookie, AUTH_TOKEN};
+use async_trait::async_trait;
+use axum::extract::{FromRequestParts, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+use tower_cookies::Cookies;
+use tracing::debug;
+
+/// Resolve the `Ctx` from the auth-token cookie and store the result in the
+/// request extensions. A valid token is refreshed with a new expiration,
+/// an invalid one is removed.
+pub async fn mw_ctx_resolve<B>(
+	State(mm): State<ModelManager>,
+	cookies: Cookies,
+	mut req: Request<B>,
+	next: Next<B>,
+) -> Result<Response, AppError> {
+	debug!("{:<12} - mw_ctx_resolve", "MIDDLEWARE");
+
+	let ctx_ext_result = _ctx_resolve(&mm, &cookies).await;
+
+	if ctx_ext_result.is_err()
+		&& !matches!(ctx_ext_result, Err(CtxExtError::TokenNotInCookie))
+	{
+		remove_token_cookie(&cookies)?;
+	}
+
+	// Store the ctx_ext_result in the request extension.
+	req.extensions_mut().insert(ctx_ext_result);
+
+	Ok(next.run(req).await)
+}
+
+async fn _ctx_resolve(mm: &ModelManager, cookies: &Cookies) -> CtxExtResult {
+	// -- Get Token String
+	let token = cookies
+		.get(AUTH_TOKEN)
+		.map(|c| c.value().to_string())
+		.ok_or(CtxExtError::TokenNotInCookie)?;
+
+	// -- Parse Token
+	let token: Token = token.parse().map_err(|_| CtxExtError::TokenWrongFormat)?;
+
+	// -- Get UserForAuth
+	let user: UserForAuth =
+		UserBmc::first_by_username(&Ctx::root_ctx(), mm, &token.ident)
+			.await
+			.map_err(|ex| CtxExtError::ModelAccessError(ex.to_string()))?
+			.ok_or(CtxExtError::UserNotFound)?;
+
+	// -- Validate Token
+	validate_web_token(&token, &user.token_salt.to_string()).map_err(
+		|ex| match ex {
+			crypt::Error::TokenExpired => CtxExtError::TokenExpired,
+			_ => CtxExtError::TokenInvalid,
+		},
+	)?;
+
+	// -- Update Token
+	set_token_cookie(cookies, &user.username, &user.token_salt.to_string())
+		.map_err(|_| CtxExtError::CannotSetTokenCookie)?;
+
+	// -- Create CtxExtResult
+	Ctx::new(user.id).map_err(|ex| CtxExtError::CtxCreateFail(ex.to_string()))
+}
+
+/// Only let through the users listed in `ADMIN_USERNAMES`.
+pub async fn mw_admin_require<B>(
+	State(mm): State<ModelManager>,
+	ctx: Ctx,
+	req: Request<B>,
+	next: Next<B>,
+) -> Result<Response, AppError> {
+	debug!("{:<12} - mw_admin_require", "MIDDLEWARE");
+
+	check_admin(&mm, &ctx).await?;
+
+	Ok(next.run(req).await)
+}
+
+/// Let through the Prometheus scraper, with `scrape_token` as bearer
+/// token, or else the admin users (as `mw_admin_require`), so no session
+/// cookie is needed to scrape.
+pub async fn mw_scrape_require<B>(
+	State((mm, scrape_token)): State<(ModelManager, Option<Secret<String>>)>,
+	ctx: Option<Ctx>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Result<Response, AppError> {
+	debug!("{:<12} - mw_scrape_require", "MIDDLEWARE");
+
+	let bearer = req
+		.headers()
+		.get(AUTHORIZATION)
+		.and_then(|val| val.to_str().ok())
+		.and_then(|val| val.strip_prefix("Bearer "));
+
+	match (bearer, ctx) {
+		(Some(bearer), _) => {
+			let token_ok = match &scrape_token {
+				Some(token) => crypt::secrets_eq(
+					config().crypt.token_key.expose(),
+					token.expose(),
+					bearer,
+				)?,
+				None => false,
+			};
+			if !token_ok {
+				return Err(Error::ScrapeTokenInvalid.into());
+			}
+		}
+		(None, Some(ctx)) => check_admin(&mm, &ctx).await?,
+		(None, None) => {
+			return Err(Error::CtxExt(CtxExtError::TokenNotInCookie).into())
+		}
+	}
+
+	Ok(next.run(req).await)
+}
+
+async fn check_admin(mm: &ModelManager, ctx: &Ctx) -> Result<(), AppError> {
+	let user: User = UserBmc::get(ctx, mm, ctx.user_id()).await?;
+	if !config().web.admin_usernames.contains(&user.username) {
+		return Err(Error::AdminRequired.into());
+	}
+
+	Ok(())
+}
+
+// region:    --- Ctx Extractor
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Ctx {
+	type Rejection = AppError;
+
+	async fn from_request_parts(
+		parts: &mut Parts,
+		_state: &S,
+	) -> Result<Self, Self::Rejection> {
+		debug!("{:<12} - Ctx", "EXTRACTOR");
+
+		parts
+			.extensions
+			.get::<CtxExtResult>()
+			.ok_or(Error::CtxExt(CtxExtError::CtxNotInRequestExt))?
+			.clone()
+			.map_err(|ex| Error::CtxExt(ex).into())
+	}
+}
+// endregion: --- Ctx Extractor
+
+// region:    --- Ctx Extractor Result/Error
+type CtxExtResult = core::result::Result<Ctx, CtxExtError>;
+
+#[derive(Clone, Serialize, Debug)]
+pub enum CtxExtError {
+	TokenNotInCookie,
+	TokenWrongFormat,
+	TokenExpired,
+	TokenInvalid,
+
+	UserNotFound,
+	ModelAccessError(String),
+	CannotSetTokenCookie,
+
+	CtxNotInRequestExt,
+	CtxCreateFail(String),
+}
+// endregion: --- Ctx Extractor Result/Error
@@ -0,0 +1,27 @@
+use crate::metrics;
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::time::Instant;
+use tracing::debug;
+
+/// Count and time every request, labelled by its route template
+/// (e.g. `/api/orders`) rather than the raw path, to bound label cardinality.
+pub async fn mw_metrics<B>(req: Request<B>, next: Next<B>) -> Response {
+	debug!("{:<12} - mw_metrics", "MIDDLEWARE");
+
+	let method = req.method().to_string();
+	let route = req
+		.extensions()
+		.get::<MatchedPath>()
+		.map(|p| p.as_str().to_string())
+		.unwrap_or_else(|| "unmatched".to_string());
+
+	let start = Instant::now();
+	let res = next.run(req).await;
+
+	metrics::observe_http(&method, &route, res.status().as_u16(), start.elapsed());
+
+	res
+}
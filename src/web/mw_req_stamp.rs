@@ -0,0 +1,49 @@
+use crate::web::error::{AppError, Error};
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::debug;
+use uuid::Uuid;
+
+/// The uuid of a request, set before any other middleware so handlers,
+/// upstream calls and the request log share the same uuid.
+#[derive(Debug, Clone)]
+pub struct ReqStamp {
+	pub uuid: Uuid,
+}
+
+pub async fn mw_req_stamp<B>(
+	mut req: Request<B>,
+	next: Next<B>,
+) -> Result<Response, AppError> {
+	debug!("{:<12} - mw_req_stamp", "MIDDLEWARE");
+
+	let uuid = Uuid::new_v4();
+
+	req.extensions_mut().insert(ReqStamp { uuid });
+
+	Ok(next.run(req).await)
+}
+
+// region:    --- ReqStamp Extractor
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ReqStamp {
+	type Rejection = AppError;
+
+	async fn from_request_parts(
+		parts: &mut Parts,
+		_state: &S,
+	) -> Result<Self, Self::Rejection> {
+		debug!("{:<12} - ReqStamp", "EXTRACTOR");
+
+		parts
+			.extensions
+			.get::<ReqStamp>()
+			.cloned()
+			.ok_or(Error::ReqStampNotInReqExt.into())
+	}
+}
+// endregion: --- ReqStamp Extractor
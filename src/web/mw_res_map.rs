@@ -0,0 +1,80 @@
+use crate::ctx::Ctx;
+use crate::log::log_request;
+use crate::web::error::AppError;
+use crate::web::mw_req_stamp::ReqStamp;
+use axum::http::{HeaderValue, Method, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::{json, to_value};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+/// Turn the `AppError` carried by the response, if any, into the client
+/// facing JSON error body with its http status code, log the request line,
+/// and return the request uuid (from `ReqStamp`) in the `X-Request-Id` header.
+pub async fn mw_response_map(
+	ctx: Option<Ctx>,
+	req_stamp: ReqStamp,
+	uri: Uri,
+	req_method: Method,
+	res: Response,
+) -> Response {
+	debug!("{:<12} - mw_response_map", "RES_MAPPER");
+	let uuid = req_stamp.uuid;
+
+	// -- Get the eventual response error.
+	let app_error = res.extensions().get::<Arc<AppError>>().cloned();
+	let client_status_error =
+		app_error.as_ref().map(|ae| ae.client_status_and_error());
+
+	// -- If client error, build the new reponse.
+	let error_response =
+		client_status_error
+			.as_ref()
+			.map(|(status_code, client_error)| {
+				let client_error = to_value(client_error).ok();
+				let error_type = client_error.as_ref().and_then(|v| v.get("type"));
+				let detail = client_error.as_ref().and_then(|v| v.get("detail"));
+
+				let client_error_body = json!({
+					"error": {
+						"type": error_type,
+						"req_uuid": uuid.to_string(),
+						"detail": detail,
+					}
+				});
+
+				debug!("CLIENT ERROR BODY:\n{client_error_body}");
+
+				// Build the new response from the client_error_body
+				(*status_code, Json(client_error_body)).into_response()
+			});
+
+	if let Some(app_error) = app_error.as_ref() {
+		error!("{req_method} {uri} - req_uuid: {uuid} - {app_error:?}");
+	}
+
+	// -- Build and log the server log line.
+	let client_error = client_status_error.map(|(_, client_error)| client_error);
+	if let Err(ex) = log_request(
+		uuid,
+		req_method,
+		uri,
+		ctx,
+		app_error.as_deref(),
+		client_error,
+	)
+	.await
+	{
+		error!("Fail to log request - {ex:?}");
+	}
+
+	let mut res = error_response.unwrap_or(res);
+	if let Ok(request_id) = HeaderValue::from_str(&uuid.to_string()) {
+		res.headers_mut().insert(X_REQUEST_ID, request_id);
+	}
+
+	res
+}
@@ -0,0 +1,76 @@
+use crate::ctx::Ctx;
+use crate::model::job::{JobBmc, JobFilter};
+use crate::model::upstream_call::{UpstreamCallBmc, UpstreamCallFilter};
+use crate::model::ModelManager;
+use crate::scheduler;
+use crate::web::error::AppError;
+use crate::web::extract::{Json, Query};
+use crate::web::mw_auth::mw_admin_require;
+use axum::extract::{Path, State};
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::Router;
+use serde_json::{json, Value};
+use tracing::debug;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/admin/upstream-calls", get(api_upstream_calls_handler))
+		.route("/api/admin/jobs", get(api_jobs_handler))
+		.route("/api/admin/jobs/:id/retry", post(api_job_retry_handler))
+		.route("/api/admin/schedules", get(api_schedules_handler))
+		.route_layer(middleware::from_fn_with_state(mm.clone(), mw_admin_require))
+		.with_state(mm)
+}
+
+// region:    --- Upstream Calls
+async fn api_upstream_calls_handler(
+	State(mm): State<ModelManager>,
+	Query(filter): Query<UpstreamCallFilter>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_upstream_calls_handler", "HANDLER");
+
+	let calls = UpstreamCallBmc::list(&mm, filter).await?;
+
+	Ok(Json(json!({ "data": calls })))
+}
+// endregion: --- Upstream Calls
+
+// region:    --- Jobs
+async fn api_jobs_handler(
+	State(mm): State<ModelManager>,
+	Query(filter): Query<JobFilter>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_jobs_handler", "HANDLER");
+
+	let jobs = JobBmc::list(&mm, filter).await?;
+
+	Ok(Json(json!({ "data": jobs })))
+}
+
+/// Run a dead-lettered job again, with its attempts reset.
+async fn api_job_retry_handler(
+	State(mm): State<ModelManager>,
+	ctx: Ctx,
+	Path(id): Path<i64>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_job_retry_handler", "HANDLER");
+
+	JobBmc::retry_now(&ctx, &mm, id).await?;
+
+	Ok(Json(json!({ "result": { "success": true } })))
+}
+// endregion: --- Jobs
+
+// region:    --- Schedules
+/// The periodic jobs, with their last run and next run.
+async fn api_schedules_handler(
+	State(mm): State<ModelManager>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_schedules_handler", "HANDLER");
+
+	let schedules = scheduler::status(&mm).await?;
+
+	Ok(Json(json!({ "data": schedules })))
+}
+// endregion: --- Schedules
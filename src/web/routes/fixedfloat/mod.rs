@@ -1,23 +1,34 @@
-use crate::clients::fixedfloat::models::CreateOrderRequest as FixedFloatCreateOrderRequest;
+use crate::clients::fixedfloat::models::OrderResponse;
 use crate::clients::FixedFloat;
 use crate::config;
+use crate::crypt::quote::Quote;
 use crate::ctx::Ctx;
-use crate::model::user::{UserBmc, UserForCreate, UserForInsert, UserForLogin};
-use crate::model::ModelManager;
-use crate::web::error::AppError;
+use crate::jobs::order_poll::OrderPoll;
+use crate::metrics;
+use crate::model::job::JobBmc;
+use crate::model::order::{OrderBmc, OrderForCreate, OrderForUpdate};
+use crate::model::{self, ModelManager};
+use crate::web::error::{AppError, Error};
+use crate::web::extract::{Json, Query};
 use crate::web::models::{
-	CreateOrderRequest, ExchangeRateRequest, OrderDetailsRequest,
+	CreateOrderRequest, ExchangeRateRequest, ListParams, OrderDetailsRequest,
 };
-use anyhow::{anyhow, Result};
+use crate::web::mw_req_stamp::ReqStamp;
+use anyhow::Result;
 use axum::extract::State;
 use axum::routing::{get, post};
-use axum::{Json, Router};
-use serde::Deserialize;
+use axum::Router;
 use serde_json::{json, Value};
-use tower_cookies::{Cookie, Cookies};
 use tracing::{debug, error, info};
 
-mod supported_currencies;
+const ORDER_TYPE: &str = "fixed";
+/// The orders are fixed on the amount received (e.g., the BTCLN invoice
+/// amount), so `amount` of the create-order request is a `to` amount.
+const ORDER_DIRECTION: &str = "to";
+/// Slippage tolerance used when the client sends a quote without one.
+const DEFAULT_SLIPPAGE_PCT: f64 = 0.5;
+/// Highest slippage tolerance a client can ask for.
+const MAX_SLIPPAGE_PCT: f64 = 5.;
 
 pub fn routes(mm: ModelManager) -> Router {
 	Router::new()
@@ -25,25 +36,31 @@ pub fn routes(mm: ModelManager) -> Router {
 		.route("/api/exchange-rate", post(api_exchange_rate_handler))
 		.route("/api/create-order", post(api_create_order_handler))
 		.route("/api/order-details", post(api_order_details_handler))
+		.route("/api/orders", get(api_orders_handler))
 		.with_state(mm)
 }
 
+/// FixedFloat client recording its calls for this request.
+async fn new_fixedfloat(mm: &ModelManager, req_stamp: &ReqStamp) -> FixedFloat {
+	FixedFloat::new(
+		&config().fixedfloat.api_key,
+		&config().fixedfloat.api_secret,
+	)
+	.await
+	.with_audit(mm.clone(), Some(req_stamp.uuid))
+}
+
 // region:    --- Currencies
 #[axum::debug_handler]
 pub async fn api_currencies_handler(
 	State(mm): State<ModelManager>,
+	req_stamp: ReqStamp,
 ) -> Result<Json<Value>, AppError> {
 	debug!("{:<12} - api_currencies_handler", "HANDLER");
 
-	let root_ctx = Ctx::root_ctx();
-
-	let fixedfloat = FixedFloat::new(
-		&config().FIXEDFLOAT_API_KEY,
-		&config().FIXEDFLOAT_API_SECRET,
-	)
-	.await;
+	let fixedfloat = new_fixedfloat(&mm, &req_stamp).await;
 
-	let currencies = match fixedfloat.get_available_currencies().await {
+	let currencies = match fixedfloat.get_available_currencies_cached().await {
 		Ok(currencies) => currencies,
 		Err(err) => {
 			error!("Error: {:?}", err);
@@ -51,7 +68,7 @@ pub async fn api_currencies_handler(
 		}
 	};
 
-	Ok(Json(json!(currencies)))
+	Ok(Json(json!(currencies.as_ref())))
 }
 // endregion: --- Currencies
 
@@ -59,23 +76,18 @@ pub async fn api_currencies_handler(
 #[axum::debug_handler]
 pub async fn api_exchange_rate_handler(
 	State(mm): State<ModelManager>,
+	req_stamp: ReqStamp,
 	Json(req): Json<ExchangeRateRequest>,
 ) -> Result<Json<Value>, AppError> {
 	debug!("{:<12} - api_exchange_rate_handler", "HANDLER");
 
-	let root_ctx = Ctx::root_ctx();
-
-	let fixedfloat = FixedFloat::new(
-		&config().FIXEDFLOAT_API_KEY,
-		&config().FIXEDFLOAT_API_SECRET,
-	)
-	.await;
+	let fixedfloat = new_fixedfloat(&mm, &req_stamp).await;
 
 	let exchange_rate = match req.direction.as_str() {
 		"from" => {
 			fixedfloat
 				.get_exchange_rate(
-					"fixed",
+					ORDER_TYPE,
 					&req.ccy,
 					"BTCLN",
 					"from",
@@ -90,7 +102,7 @@ pub async fn api_exchange_rate_handler(
 		"to" => {
 			fixedfloat
 				.get_exchange_rate(
-					"fixed",
+					ORDER_TYPE,
 					"BTCLN",
 					&req.ccy,
 					"to",
@@ -103,7 +115,9 @@ pub async fn api_exchange_rate_handler(
 				.await
 		}
 		_ => {
-			let err = AppError::from(anyhow!("Invalid direction"));
+			let err = AppError::from(Error::InvalidDirection {
+				direction: req.direction.to_string(),
+			});
 			error!("Error: {:?}", err);
 			return Err(err);
 		}
@@ -117,64 +131,81 @@ pub async fn api_exchange_rate_handler(
 		}
 	};
 
-	Ok(Json(json!(exchange_rate)))
+	// -- Sign a quote the client can reference when creating the order.
+	let quote = Quote::new(ORDER_TYPE, &req.direction, &req.amount, &exchange_rate)
+		.to_token()?;
+
+	let mut res = json!(exchange_rate);
+	res["quote"] = json!(quote);
+
+	Ok(Json(res))
 }
+// endregion: --- Exchange Rate
 
 // region:    --- Create Order
 #[axum::debug_handler]
 pub async fn api_create_order_handler(
 	State(mm): State<ModelManager>,
+	req_stamp: ReqStamp,
+	ctx: Ctx,
 	Json(req): Json<CreateOrderRequest>,
 ) -> Result<Json<Value>, AppError> {
 	debug!("{:<12} - api_create_order_handler", "HANDLER");
-	let root_ctx = Ctx::root_ctx();
 
 	info!("req: \n{:?}", req);
 
-	let fixedfloat = FixedFloat::new(
-		&config().FIXEDFLOAT_API_KEY,
-		&config().FIXEDFLOAT_API_SECRET,
-	)
-	.await;
+	let fixedfloat = new_fixedfloat(&mm, &req_stamp).await;
 
-	let order_response = match req.direction.as_str() {
-		"from" => {
-			fixedfloat
-				.create_order(
-					"fixed".to_string(),
-					req.ccy,
-					"BTCLN".to_string(),
-					"to".to_string(),
-					req.amount,
-					req.to_address,
-					None,
-					None,
-					None,
-				)
-				.await
-		}
-		"to" => {
-			fixedfloat
-				.create_order(
-					"fixed".to_string(),
-					"BTCLN".to_string(),
-					req.ccy,
-					"to".to_string(),
-					req.amount,
-					req.to_address,
-					None,
-					None,
-					None,
-				)
-				.await
-		}
+	let (from_ccy, to_ccy) = match req.direction.as_str() {
+		"from" => (req.ccy.to_string(), "BTCLN".to_string()),
+		"to" => ("BTCLN".to_string(), req.ccy.to_string()),
 		_ => {
-			let err = AppError::from(anyhow!("Invalid direction"));
+			let err = AppError::from(Error::InvalidDirection {
+				direction: req.direction.to_string(),
+			});
 			error!("Error: {:?}", err);
 			return Err(err);
 		}
 	};
 
+	// -- Recheck the quoted price against the fresh price of the order.
+	if let Some((quote, slippage_pct)) = check_quote(&req, &from_ccy, &to_ccy)? {
+		let fresh_rate = fixedfloat
+			.get_exchange_rate(
+				ORDER_TYPE,
+				&from_ccy,
+				&to_ccy,
+				ORDER_DIRECTION,
+				&req.amount,
+				None,
+				None,
+				None,
+				None,
+			)
+			.await?;
+
+		if let Err(err) =
+			quote.check_slippage(&fresh_rate, ORDER_DIRECTION, slippage_pct)
+		{
+			error!("Error: {:?}", err);
+			return Err(AppError::from(err));
+		}
+	}
+
+	let order_response = fixedfloat
+		.create_order(
+			ORDER_TYPE.to_string(),
+			from_ccy,
+			to_ccy,
+			ORDER_DIRECTION.to_string(),
+			req.amount,
+			req.to_address.clone(),
+			None,
+			None,
+			None,
+		)
+		.await;
+
 	let order_response = match order_response {
 		Ok(order_response) => order_response,
 		Err(err) => {
@@ -183,7 +214,57 @@ pub async fn api_create_order_handler(
 		}
 	};
 
-	Ok(Json(json!(order_response)))
+	// -- Persist the order for the ctx user, with the job polling it.
+	let order = &order_response.data;
+	let txn = mm.begin().await?;
+	let order_id = OrderBmc::create(
+		&ctx,
+		&txn,
+		OrderForCreate {
+			ff_id: order.id.to_string(),
+			ff_token: order.token.expose().to_string(),
+			order_type: order.order_type.to_string(),
+			from_ccy: order.from.code.to_string(),
+			to_ccy: order.to.code.to_string(),
+			from_amount: order.from.amount.to_string(),
+			to_amount: order.to.amount.to_string(),
+			to_address: req.to_address,
+			status: order.status.to_string(),
+		},
+	)
+	.await?;
+	JobBmc::enqueue(&ctx, &txn, OrderPoll::job(order_id)).await?;
+	txn.commit().await?;
+	metrics::order_created(&order.from.code, &order.to.code, &order.order_type);
+
+	Ok(Json(order_response_json(&order_response)))
+}
+
+/// The quote of the request, if any, with its slippage tolerance, once
+/// checked to be for the order to place (pair, and amount received).
+fn check_quote(
+	req: &CreateOrderRequest,
+	from_ccy: &str,
+	to_ccy: &str,
+) -> Result<Option<(Quote, f64)>, AppError> {
+	let Some(quote) = req.quote.as_deref() else {
+		return Ok(None);
+	};
+
+	let quote = Quote::from_token(quote)?;
+	quote.check_pair(from_ccy, to_ccy)?;
+	quote.check_order(ORDER_DIRECTION, &req.amount)?;
+
+	// Also rejects NaN.
+	let slippage_pct = req.slippage_pct.unwrap_or(DEFAULT_SLIPPAGE_PCT);
+	if !(slippage_pct > 0. && slippage_pct <= MAX_SLIPPAGE_PCT) {
+		return Err(AppError::from(Error::SlippageInvalid {
+			slippage_pct,
+			max_pct: MAX_SLIPPAGE_PCT,
+		}));
+	}
+
+	Ok(Some((quote, slippage_pct)))
 }
 // endregion: --- Create Order
 
@@ -191,18 +272,22 @@ pub async fn api_create_order_handler(
 #[axum::debug_handler]
 pub async fn api_order_details_handler(
 	State(mm): State<ModelManager>,
+	req_stamp: ReqStamp,
+	ctx: Ctx,
 	Json(req): Json<OrderDetailsRequest>,
 ) -> Result<Json<Value>, AppError> {
 	debug!("{:<12} - api_order_details_handler", "HANDLER");
-	let root_ctx = Ctx::root_ctx();
 
 	info!("req: \n{:?}", req);
 
-	let fixedfloat = FixedFloat::new(
-		&config().FIXEDFLOAT_API_KEY,
-		&config().FIXEDFLOAT_API_SECRET,
-	)
-	.await;
+	// -- Only the owner can follow the order.
+	let order = OrderBmc::first_by_ff_id(&ctx, &mm, &req.id).await?.ok_or(
+		Error::OrderNotFound {
+			ff_id: req.id.to_string(),
+		},
+	)?;
+
+	let fixedfloat = new_fixedfloat(&mm, &req_stamp).await;
 
 	let order_response = match fixedfloat.order_details(&req.id, &req.token).await {
 		Ok(order) => order,
@@ -212,5 +297,198 @@ pub async fn api_order_details_handler(
 		}
 	};
 
-	Ok(Json(json!(order_response)))
+	// -- Keep the stored status in sync. On a version conflict, the order
+	//    was updated concurrently (e.g., by the poller), so leave it be.
+	if order_response.data.status != order.status {
+		let res = OrderBmc::update(
+			&ctx,
+			&mm,
+			order.id,
+			order.version,
+			OrderForUpdate {
+				status: Some(order_response.data.status.to_string()),
+			},
+		)
+		.await;
+		match res {
+			Ok(_) => {}
+			Err(model::Error::VersionConflict { current, .. }) => {
+				debug!("order {} updated concurrently (v{current})", order.id);
+			}
+			Err(err) => return Err(AppError::from(err)),
+		}
+	}
+
+	Ok(Json(order_response_json(&order_response)))
+}
+
+/// The order response for the client, which needs the order token
+/// to follow the order.
+fn order_response_json(order_response: &OrderResponse) -> Value {
+	let mut res = json!(order_response);
+	res["data"]["token"] = json!(order_response.data.token.expose());
+	res
+}
+// endregion: --- Order Details
+
+// region:    --- Orders
+#[axum::debug_handler]
+pub async fn api_orders_handler(
+	State(mm): State<ModelManager>,
+	ctx: Ctx,
+	Query(params): Query<ListParams>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_orders_handler", "HANDLER");
+
+	let (filter, list_options) = params.parse()?;
+
+	let total = OrderBmc::count(&ctx, &mm, filter.clone()).await?;
+	let orders = OrderBmc::list(&ctx, &mm, filter, list_options).await?;
+
+	Ok(Json(json!({ "data": orders, "total": total })))
+}
+// endregion: --- Orders
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::user::{User, UserBmc};
+	use crate::web::error::ClientError;
+	use axum::http::StatusCode;
+
+	async fn fx_mm_and_ctx() -> Result<(ModelManager, Ctx)> {
+		let mm = ModelManager::new_scratch().await?;
+		let demo: User = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, "demo")
+			.await?
+			.ok_or(anyhow::anyhow!("seed user 'demo' missing"))?;
+
+		Ok((mm, Ctx::new(demo.id)?))
+	}
+
+	fn fx_order(ff_id: &str, status: &str) -> OrderForCreate {
+		OrderForCreate {
+			ff_id: ff_id.to_string(),
+			ff_token: "fx-token".to_string(),
+			order_type: "fixed".to_string(),
+			from_ccy: "BTC".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			from_amount: "0.01".to_string(),
+			to_amount: "0.0099".to_string(),
+			to_address: "lnbc1fx".to_string(),
+			status: status.to_string(),
+		}
+	}
+
+	/// The create-order body of the web UI receive flow (see `web-folder`),
+	/// for a `from` quote of `amount` USDCETH.
+	fn fx_ui_create_order(amount: &str) -> Result<CreateOrderRequest> {
+		let quote = Quote {
+			order_type: ORDER_TYPE.to_string(),
+			from_ccy: "USDCETH".to_string(),
+			to_ccy: "BTCLN".to_string(),
+			direction: "from".to_string(),
+			amount: "100".to_string(),
+			from_amount: "100".to_string(),
+			to_amount: "0.00151".to_string(),
+			exp: crate::utils::now_utc_plus_sec_str(60.),
+		};
+		let body = json!({
+			"direction": "from",
+			"ccy": "USDCETH",
+			"amount": amount,
+			"toAddress": "lnbc1fx",
+			"quote": quote.to_token()?,
+		});
+
+		Ok(serde_json::from_value(body)?)
+	}
+
+	#[test]
+	fn test_check_quote_ui_receive_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let req = fx_ui_create_order("0.00151")?;
+
+		// -- Exec
+		let checked = check_quote(&req, "USDCETH", "BTCLN")
+			.map_err(|ex| anyhow::anyhow!("{ex}"))?;
+
+		// -- Check
+		let (quote, slippage_pct) = checked.ok_or(anyhow::anyhow!("no quote"))?;
+		assert_eq!(quote.to_amount, "0.00151");
+		assert_eq!(slippage_pct, DEFAULT_SLIPPAGE_PCT);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_check_quote_err_sent_amount() -> Result<()> {
+		// -- Setup & Fixtures
+		// The quoted amount sent, while the order is fixed on the received one.
+		let req = fx_ui_create_order("100")?;
+
+		// -- Exec
+		let res = check_quote(&req, "USDCETH", "BTCLN");
+
+		// -- Check
+		let Err(err) = res else {
+			return Err(anyhow::anyhow!("should fail"));
+		};
+		let (status, client_error) = err.client_status_and_error();
+		assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+		assert!(matches!(client_error, ClientError::QUOTE_MISMATCH));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_api_orders_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		for (ff_id, status) in [("FX01", "NEW"), ("FX02", "DONE"), ("FX03", "NEW")] {
+			OrderBmc::create(&ctx, &mm, fx_order(ff_id, status)).await?;
+		}
+		let params = ListParams {
+			filter: Some(r#"{"status": {"$eq": "NEW"}}"#.to_string()),
+			list_options: Some(
+				r#"{"limit": 1, "order_bys": ["!ff_id"]}"#.to_string(),
+			),
+		};
+
+		// -- Exec
+		let Json(res) = api_orders_handler(State(mm), ctx, Query(params))
+			.await
+			.map_err(|ex| anyhow::anyhow!("{ex}"))?;
+
+		// -- Check
+		assert_eq!(res["total"], 2);
+		assert_eq!(res["data"][0]["ff_id"], "FX03");
+		assert!(res["data"][0].get("ff_token").is_none(), "ff_token leaked");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_api_orders_err_invalid_filter() -> Result<()> {
+		// -- Setup & Fixtures
+		let (mm, ctx) = fx_mm_and_ctx().await?;
+		let params = ListParams {
+			filter: Some(r#"{"id": {"$eq": "one"}}"#.to_string()),
+			list_options: None,
+		};
+
+		// -- Exec
+		let res = api_orders_handler(State(mm), ctx, Query(params)).await;
+
+		// -- Check
+		let Err(err) = res else {
+			return Err(anyhow::anyhow!("should fail"));
+		};
+		let (status, client_error) = err.client_status_and_error();
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert!(matches!(client_error, ClientError::INVALID_PARAMS { .. }));
+
+		Ok(())
+	}
 }
+// endregion: --- Tests
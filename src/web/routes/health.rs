@@ -0,0 +1,119 @@
+use crate::clients::fixedfloat::cache::currencies_age;
+use crate::model::upstream_call::UpstreamCallBmc;
+use crate::model::ModelManager;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// The cached currency list older than this is stale. Each instance
+/// refreshes its own cache well within, from startup on (the
+/// `currencies_refresh` job, of `scheduler::Scope::Instance`).
+const CURRENCIES_MAX_AGE: Duration = Duration::from_secs(900);
+/// Window over which the upstream success rate is computed.
+const UPSTREAM_WINDOW_SEC: i64 = 300;
+/// Below this share of answered upstream calls, the service is not ready.
+const UPSTREAM_MIN_SUCCESS_RATE: f64 = 0.5;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/health/live", get(health_live_handler))
+		.route("/health/ready", get(health_ready_handler))
+		.with_state(mm)
+}
+
+/// The process is up and serving requests.
+async fn health_live_handler() -> Json<Value> {
+	Json(json!({ "status": "live" }))
+}
+
+/// Ready when the database, the currency list and FixedFloat are all usable.
+/// Answers 503 with the same breakdown otherwise.
+///
+/// Unauthenticated, so the checks only carry their status, the failure
+/// details are logged. Never calls FixedFloat itself.
+async fn health_ready_handler(
+	State(mm): State<ModelManager>,
+) -> (StatusCode, Json<Value>) {
+	debug!("{:<12} - health_ready_handler", "HANDLER");
+
+	let db = check_db(&mm).await;
+	let currencies = check_currencies();
+	let upstream = check_upstream(&mm).await;
+
+	let ready = [&db, &currencies, &upstream]
+		.iter()
+		.all(|check| check["ok"] == json!(true));
+	if !ready {
+		warn!(
+			"{:<12} - not ready - {db} - {currencies} - {upstream}",
+			"HEALTH"
+		);
+	}
+
+	let status = if ready {
+		StatusCode::OK
+	} else {
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+	let body = json!({
+		"status": if ready { "ready" } else { "not_ready" },
+		"checks": {
+			"db": db,
+			"currencies": currencies,
+			"upstream": upstream,
+		}
+	});
+
+	(status, Json(body))
+}
+
+// region:    --- Checks
+async fn check_db(mm: &ModelManager) -> Value {
+	match mm.ping().await {
+		Ok(()) => json!({ "ok": true }),
+		Err(ex) => {
+			warn!("{:<12} - db check failed - {ex}", "HEALTH");
+			json!({ "ok": false })
+		}
+	}
+}
+
+/// The currency list cache of this process must be present and fresh.
+fn check_currencies() -> Value {
+	let age = currencies_age();
+
+	json!({
+		"ok": age.is_some_and(|age| age < CURRENCIES_MAX_AGE),
+		"age_sec": age.map(|age| age.as_secs()),
+	})
+}
+
+/// With no recent calls, there is nothing to hold against FixedFloat.
+async fn check_upstream(mm: &ModelManager) -> Value {
+	let (calls, answered) =
+		match UpstreamCallBmc::answered_since(mm, UPSTREAM_WINDOW_SEC).await {
+			Ok(counts) => counts,
+			Err(ex) => {
+				warn!("{:<12} - upstream check failed - {ex}", "HEALTH");
+				return json!({ "ok": false });
+			}
+		};
+
+	let success_rate = if calls == 0 {
+		1.
+	} else {
+		answered as f64 / calls as f64
+	};
+
+	json!({
+		"ok": success_rate >= UPSTREAM_MIN_SUCCESS_RATE,
+		"success_rate": success_rate,
+		"calls": calls,
+		"window_sec": UPSTREAM_WINDOW_SEC,
+	})
+}
+// endregion: --- Checks
@@ -0,0 +1,107 @@
+use crate::ctx::Ctx;
+use crate::model::user::{UserBmc, UserForLogin};
+use crate::model::ModelManager;
+use crate::web::error::{AppError, Error};
+use crate::web::extract::Json;
+use crate::web::{self, remove_token_cookie};
+use axum::extract::State;
+use axum::routing::post;
+use axum::Router;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use tracing::debug;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/login", post(api_login_handler))
+		.route("/api/logoff", post(api_logoff_handler))
+		.with_state(mm)
+}
+
+// region:    --- Login
+#[derive(Debug, Deserialize)]
+struct LoginPayload {
+	username: String,
+	pwd: String,
+}
+
+async fn api_login_handler(
+	State(mm): State<ModelManager>,
+	cookies: Cookies,
+	Json(payload): Json<LoginPayload>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_login_handler", "HANDLER");
+
+	let LoginPayload {
+		username,
+		pwd: pwd_clear,
+	} = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Get the user.
+	let user: UserForLogin = UserBmc::first_by_username(&root_ctx, &mm, &username)
+		.await?
+		.ok_or(Error::LoginFailUsernameNotFound)?;
+	let user_id = user.id;
+
+	// -- Validate the password.
+	if user.pwd.is_none() {
+		return Err(Error::LoginFailUserHasNoPwd { user_id }.into());
+	}
+	UserBmc::validate_pwd(&root_ctx, &mm, &user, &pwd_clear)
+		.await
+		.map_err(|_| Error::LoginFailPwdNotMatching { user_id })?;
+
+	// -- Set web token.
+	web::set_token_cookie(&cookies, &user.username, &user.token_salt.to_string())?;
+
+	// Create the success body.
+	let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+	Ok(body)
+}
+// endregion: --- Login
+
+// region:    --- Logoff
+#[derive(Debug, Deserialize)]
+struct LogoffPayload {
+	logoff: bool,
+}
+
+/// Remove the auth-token cookie and, for an authenticated caller, rotate
+/// the user token salt, which revokes every token issued for them so far.
+async fn api_logoff_handler(
+	State(mm): State<ModelManager>,
+	ctx: Option<Ctx>,
+	cookies: Cookies,
+	Json(payload): Json<LogoffPayload>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_logoff_handler", "HANDLER");
+
+	let should_logoff = payload.logoff;
+
+	if should_logoff {
+		// The ctx comes from a validated token, so a forged cookie
+		// cannot revoke someone else's tokens.
+		if let Some(ctx) = ctx {
+			UserBmc::rotate_token_salt(&ctx, &mm, ctx.user_id()).await?;
+		}
+
+		remove_token_cookie(&cookies)?;
+	}
+
+	// Create the success body.
+	let body = Json(json!({
+		"result": {
+			"logged_off": should_logoff
+		}
+	}));
+
+	Ok(body)
+}
+// endregion: --- Logoff
@@ -0,0 +1,103 @@
+use crate::config;
+use crate::metrics;
+use crate::model::ModelManager;
+use crate::utils::Secret;
+use crate::web::error::AppError;
+use crate::web::mw_auth::mw_scrape_require;
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::middleware;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use tracing::debug;
+
+const CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4";
+
+pub fn routes(mm: ModelManager) -> Router {
+	routes_with_token(mm, config().web.metrics_token.clone())
+}
+
+fn routes_with_token(
+	mm: ModelManager,
+	scrape_token: Option<Secret<String>>,
+) -> Router {
+	Router::new()
+		.route("/metrics", get(metrics_handler))
+		.route_layer(middleware::from_fn_with_state(
+			(mm.clone(), scrape_token),
+			mw_scrape_require,
+		))
+		.with_state(mm)
+}
+
+/// Prometheus scrape endpoint, in the text exposition format.
+/// For the scraper (`web.metrics_token` bearer token) and the admin users
+/// only, as the metrics expose order and upstream activity.
+async fn metrics_handler(
+	State(mm): State<ModelManager>,
+) -> Result<impl IntoResponse, AppError> {
+	debug!("{:<12} - metrics_handler", "HANDLER");
+
+	let body = metrics::render(&mm).await?;
+
+	Ok(([(CONTENT_TYPE, CONTENT_TYPE_TEXT)], body))
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::header::AUTHORIZATION;
+	use axum::http::{Request, StatusCode};
+	use std::sync::Arc;
+	use tower::ServiceExt;
+
+	fn fx_scrape(bearer: Option<&str>) -> Result<Request<Body>> {
+		let mut req = Request::get("/metrics");
+		if let Some(bearer) = bearer {
+			req = req.header(AUTHORIZATION, format!("Bearer {bearer}"));
+		}
+
+		Ok(req.body(Body::empty())?)
+	}
+
+	#[tokio::test]
+	async fn test_scrape_token_no_cookie_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let routes = routes_with_token(mm, Some(Secret::new("fx-scrape".into())));
+
+		// -- Exec
+		let res = routes.oneshot(fx_scrape(Some("fx-scrape"))?).await?;
+
+		// -- Check
+		assert_eq!(res.status(), StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_scrape_err_unauthorized() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = ModelManager::new_scratch().await?;
+		let routes = routes_with_token(mm, Some(Secret::new("fx-scrape".into())));
+
+		// -- Exec & Check
+		// (The status is set from the `AppError` by `mw_response_map`)
+		for bearer in [Some("fx-wrong"), None] {
+			let res = routes.clone().oneshot(fx_scrape(bearer)?).await?;
+			let app_error = res
+				.extensions()
+				.get::<Arc<AppError>>()
+				.ok_or(anyhow::anyhow!("no AppError for {bearer:?}"))?;
+			let (status, _) = app_error.client_status_and_error();
+			assert_eq!(status, StatusCode::UNAUTHORIZED, "{bearer:?}");
+		}
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
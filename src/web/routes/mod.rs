@@ -1,3 +1,9 @@
+pub mod admin;
 pub mod fixedfloat;
+pub mod health;
+pub mod login;
+pub mod metrics;
+pub mod rates;
 pub mod static_files;
+pub mod users;
 pub mod utils;
@@ -0,0 +1,36 @@
+use crate::model::rate_snapshot::RateSnapshotBmc;
+use crate::model::ModelManager;
+use crate::web::error::AppError;
+use crate::web::extract::{Json, Query};
+use crate::web::models::RateHistoryParams;
+use axum::extract::State;
+use axum::routing::get;
+use axum::Router;
+use serde_json::{json, Value};
+use tracing::debug;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/rates/history", get(api_rate_history_handler))
+		.with_state(mm)
+}
+
+// region:    --- Rate History
+/// OHLC candles of the captured rates of a pair (see `rates.snapshot_pairs`).
+async fn api_rate_history_handler(
+	State(mm): State<ModelManager>,
+	Query(params): Query<RateHistoryParams>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_rate_history_handler", "HANDLER");
+
+	let (filter, interval) = params.parse()?;
+	let candles = RateSnapshotBmc::history(&mm, &filter, interval).await?;
+
+	Ok(Json(json!({
+		"pair": format!("{}/{}", filter.from_ccy, filter.to_ccy),
+		"type": filter.order_type,
+		"interval_sec": interval.as_secs(),
+		"data": candles,
+	})))
+}
+// endregion: --- Rate History
@@ -0,0 +1,191 @@
+use crate::ctx::Ctx;
+use crate::model::user::{UserBmc, UserForAuth, UserForCreate, UserForLogin};
+use crate::model::{self, ModelManager};
+use crate::web::error::{AppError, Error};
+use crate::web::extract::Json;
+use crate::web::{self, remove_token_cookie};
+use axum::extract::State;
+use axum::routing::{delete, post, put};
+use axum::Router;
+use lazy_regex::regex_is_match;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use tracing::debug;
+
+const PWD_MIN_LEN: usize = 8;
+const PWD_MAX_LEN: usize = 128;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/users", post(api_user_create_handler))
+		.route("/api/users/me", delete(api_user_delete_handler))
+		.route("/api/users/me/pwd", put(api_user_update_pwd_handler))
+		.with_state(mm)
+}
+
+// region:    --- Sign-up
+#[derive(Debug, Deserialize)]
+struct UserCreatePayload {
+	username: String,
+	pwd: String,
+}
+
+async fn api_user_create_handler(
+	State(mm): State<ModelManager>,
+	cookies: Cookies,
+	Json(payload): Json<UserCreatePayload>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_user_create_handler", "HANDLER");
+
+	let UserCreatePayload { username, pwd } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	validate_username(&username)?;
+	validate_pwd_strength(&username, &pwd)?;
+
+	let id = UserBmc::create(
+		&root_ctx,
+		&mm,
+		UserForCreate {
+			username: username.to_string(),
+			pwd_clear: pwd,
+		},
+	)
+	.await
+	.map_err(|ex| -> AppError {
+		match ex {
+			model::Error::UniqueViolation { .. } => {
+				Error::UserUsernameTaken { username }.into()
+			}
+			ex => ex.into(),
+		}
+	})?;
+
+	// -- Log the new user in.
+	let user: UserForAuth = UserBmc::get(&root_ctx, &mm, id).await?;
+	web::set_token_cookie(&cookies, &user.username, &user.token_salt.to_string())?;
+
+	let body = Json(json!({
+		"data": {
+			"id": user.id,
+			"username": user.username,
+		}
+	}));
+
+	Ok(body)
+}
+// endregion: --- Sign-up
+
+// region:    --- Change Password
+#[derive(Debug, Deserialize)]
+struct UserUpdatePwdPayload {
+	pwd_old: String,
+	pwd_new: String,
+}
+
+/// Change the ctx user pwd. Other sessions are revoked,
+/// and the current one gets a fresh token.
+async fn api_user_update_pwd_handler(
+	State(mm): State<ModelManager>,
+	cookies: Cookies,
+	ctx: Ctx,
+	Json(payload): Json<UserUpdatePwdPayload>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_user_update_pwd_handler", "HANDLER");
+
+	let UserUpdatePwdPayload { pwd_old, pwd_new } = payload;
+
+	let user: UserForLogin = UserBmc::get(&ctx, &mm, ctx.user_id()).await?;
+	UserBmc::validate_pwd(&ctx, &mm, &user, &pwd_old)
+		.await
+		.map_err(|_| Error::UserPwdNotMatching)?;
+
+	validate_pwd_strength(&user.username, &pwd_new)?;
+
+	UserBmc::update_pwd(&ctx, &mm, user.id, &pwd_new).await?;
+
+	// -- Revoke other sessions, and re-issue the current token.
+	UserBmc::rotate_token_salt(&ctx, &mm, user.id).await?;
+	let user: UserForAuth = UserBmc::get(&ctx, &mm, user.id).await?;
+	web::set_token_cookie(&cookies, &user.username, &user.token_salt.to_string())?;
+
+	let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+	Ok(body)
+}
+// endregion: --- Change Password
+
+// region:    --- Delete Account
+#[derive(Debug, Deserialize)]
+struct UserDeletePayload {
+	pwd: String,
+}
+
+/// Delete the ctx user account (and its orders), after confirming the pwd.
+async fn api_user_delete_handler(
+	State(mm): State<ModelManager>,
+	cookies: Cookies,
+	ctx: Ctx,
+	Json(payload): Json<UserDeletePayload>,
+) -> Result<Json<Value>, AppError> {
+	debug!("{:<12} - api_user_delete_handler", "HANDLER");
+
+	let user: UserForLogin = UserBmc::get(&ctx, &mm, ctx.user_id()).await?;
+	UserBmc::validate_pwd(&ctx, &mm, &user, &payload.pwd)
+		.await
+		.map_err(|_| Error::UserPwdNotMatching)?;
+
+	UserBmc::delete(&ctx, &mm, user.id).await?;
+
+	remove_token_cookie(&cookies)?;
+
+	let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+	Ok(body)
+}
+// endregion: --- Delete Account
+
+// region:    --- Validations
+fn validate_username(username: &str) -> Result<(), Error> {
+	if !regex_is_match!(r"^[a-zA-Z][a-zA-Z0-9_.\-]{2,31}$", username) {
+		return Err(Error::UserUsernameInvalid {
+			reason:
+				"3 to 32 letters, digits, '_', '.' or '-', starting with a letter",
+		});
+	}
+
+	Ok(())
+}
+
+fn validate_pwd_strength(username: &str, pwd: &str) -> Result<(), Error> {
+	let len = pwd.chars().count();
+
+	let reason = if len < PWD_MIN_LEN {
+		Some("must be at least 8 characters")
+	} else if len > PWD_MAX_LEN {
+		Some("must be at most 128 characters")
+	} else if !pwd.chars().any(|c| c.is_alphabetic()) {
+		Some("must contain a letter")
+	} else if !pwd.chars().any(|c| c.is_ascii_digit()) {
+		Some("must contain a digit")
+	} else if pwd.to_lowercase().contains(&username.to_lowercase()) {
+		Some("must not contain the username")
+	} else {
+		None
+	};
+
+	match reason {
+		Some(reason) => Err(Error::UserPwdTooWeak { reason }),
+		None => Ok(()),
+	}
+}
+// endregion: --- Validations
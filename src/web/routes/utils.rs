@@ -4,11 +4,8 @@ use axum::{
 	Router,
 };
 use qrcode::{render::svg, QrCode};
-use reqwest::StatusCode;
 
-use crate::model::ModelManager;
-
-pub fn routes(mm: ModelManager) -> Router {
+pub fn routes() -> Router {
 	Router::new().route("/api/qrcode", post(api_qrcode_handler))
 }
 
@@ -1,10 +1,12 @@
 let isLoading = false;
+let isLoggedIn = false;
 
 // Get DOM elements
 const toggle = document.getElementById("toggle");
 const select = document.getElementById("currency-codes");
 const address = document.getElementById("address");
 const form = document.getElementById("currency-form");
+const loginForm = document.getElementById("login-form");
 const responseDiv = document.querySelector(".response");
 responseDiv.classList.add("hidden");
 
@@ -14,7 +16,9 @@ const updateLoadingState = () => {
     responseDiv.classList.remove("hidden");
     responseDiv.textContent = "Loading...";
   } else {
-    form.classList.remove("hidden");
+    if (isLoggedIn) {
+      form.classList.remove("hidden");
+    }
     responseDiv.classList.add("hidden");
     responseDiv.textContent = "";
   }
@@ -173,9 +177,33 @@ const handleFormSubmit = async (event) => {
   updateLoadingState();
 };
 
+const handleLoginSubmit = async (event) => {
+  event.preventDefault();
+
+  const data = await fetchData(`${API_URL}/login`, {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/json",
+    },
+    body: JSON.stringify({
+      username: document.getElementById("username").value,
+      pwd: document.getElementById("pwd").value,
+    }),
+  });
+
+  if (data && data["result"] && data["result"]["success"]) {
+    isLoggedIn = true;
+    loginForm.classList.add("hidden");
+    form.classList.remove("hidden");
+  } else {
+    alert("Login failed!");
+  }
+};
+
 // Event listeners
 toggle.addEventListener("change", handleToggleChange);
 form.addEventListener("submit", handleFormSubmit);
+loginForm.addEventListener("submit", handleLoginSubmit);
 
 // Initial setup
 window.onload = async function () {
@@ -195,7 +223,12 @@ async function handleReceivePayment(
     const { paymentRequest } = await window.webln.makeInvoice(amountInSatoshis);
 
     if (!!paymentRequest) {
-      await createReceiveOrder(currencyCode, btcAmount, paymentRequest);
+      await createReceiveOrder(
+        currencyCode,
+        data["data"]["to"]["amount"],
+        paymentRequest,
+        data["quote"]
+      );
     } else {
       console.log("Failed to create invoice!");
     }
@@ -205,12 +238,19 @@ async function handleReceivePayment(
   }
 }
 
-async function createReceiveOrder(currencyCode, btcAmount, paymentRequest) {
+async function createReceiveOrder(
+  currencyCode,
+  toAmount,
+  paymentRequest,
+  quote
+) {
+  // The order is fixed on the amount received, as quoted (and invoiced).
   const requestBody = {
     direction: "from",
     ccy: currencyCode,
-    amount: String(btcAmount),
+    amount: toAmount,
     toAddress: paymentRequest,
+    quote: quote,
   };
 
   const data = await fetchData(`${API_URL}/create-order`, {